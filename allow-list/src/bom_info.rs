@@ -0,0 +1,67 @@
+use crate::cli::BomOptions;
+use crate::licenses::{parse_bom, read_bom_content, spec_version};
+use std::path::Path;
+
+/// Print a summary of the metadata in a CycloneDX BOM
+pub(crate) fn bom_info<W>(bom_path: &Path, mut w: W) -> Result<(), anyhow::Error>
+where
+    W: std::io::Write,
+{
+    let content = read_bom_content(bom_path)?;
+    let spec_version = spec_version(&content).unwrap_or_else(|| "unknown".to_string());
+    let bom = parse_bom(bom_path, &content, &BomOptions::default())?;
+
+    let metadata = bom.metadata.as_ref();
+    let timestamp = metadata
+        .and_then(|x| x.timestamp.as_ref())
+        .map(|x| x.to_string());
+    let subject = metadata
+        .and_then(|x| x.component.as_ref())
+        .map(|x| match x.version.as_ref() {
+            Some(version) => format!("{} {}", x.name, version),
+            None => x.name.to_string(),
+        });
+    let count = bom.components.as_ref().map(|x| x.0.len()).unwrap_or(0);
+
+    writeln!(w, "spec version: {spec_version}")?;
+    writeln!(w, "timestamp: {}", timestamp.as_deref().unwrap_or("none"))?;
+    writeln!(w, "subject: {}", subject.as_deref().unwrap_or("none"))?;
+    writeln!(w, "components: {count}")?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{bom, TempDir};
+
+    #[test]
+    fn prints_the_metadata_of_a_fixture() {
+        let mut out = Vec::new();
+        bom_info(Path::new("fixtures/bom-1.5.json"), &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "spec version: 1.5\n\
+             timestamp: 2024-03-01T12:00:00.000000000Z\n\
+             subject: app 0.1.0\n\
+             components: 2\n"
+        );
+    }
+
+    #[test]
+    fn counts_components_without_metadata() {
+        let dir = TempDir::new("bom-info");
+        let path = dir.write(
+            "bom.json",
+            &bom(&[("serde", "1.0.188"), ("libc", "0.2.150")]),
+        );
+
+        let mut out = Vec::new();
+        bom_info(&path, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("spec version: 1.4\n"));
+        assert!(out.contains("subject: none\n"));
+        assert!(out.ends_with("components: 2\n"));
+    }
+}
@@ -1,9 +1,19 @@
-use clap::{Parser, Subcommand};
+use crate::config::Config;
+use clap::{Args, Parser, Subcommand, ValueEnum};
 
 #[derive(Parser)]
 #[clap(author, version, about, long_about = None)]
 #[clap(propagate_version = true)]
 pub(crate) struct Cli {
+    /// exit with an error if any warnings were emitted
+    #[clap(long, global = true)]
+    pub(crate) werror: bool,
+    /// never color diagnostics, even when stderr is a terminal
+    #[clap(long, global = true)]
+    pub(crate) no_color: bool,
+    /// print diagnostics to stderr: -v for progress, -vv also for every skipped component
+    #[clap(short, long, global = true, action = clap::ArgAction::Count)]
+    pub(crate) verbose: u8,
     #[clap(subcommand)]
     pub(crate) command: Commands,
 }
@@ -12,12 +22,18 @@ pub(crate) struct Cli {
 pub(crate) enum Commands {
     /// outputs a human-readable report of all 3rd party licenses
     GenLicenses {
-        /// path to the cyclonedx JSON
+        /// path to the cyclonedx BOM, or - to read it from stdin
         #[clap(value_parser, long, short = 'b')]
         bom_path: std::path::PathBuf,
-        /// path to the JSON configuration (allow-list)
-        #[clap(value_parser, long, short = 'c')]
-        config_path: std::path::PathBuf,
+        #[clap(flatten)]
+        config_options: ConfigOptions,
+        /// write the report to this file instead of stdout
+        #[clap(long, value_name = "PATH")]
+        output: Option<std::path::PathBuf>,
+        #[clap(flatten)]
+        bom_options: BomOptions,
+        #[clap(flatten)]
+        options: ReportOptions,
     },
     /// outputs a human-readable report of all 3rd party licenses
     GenLicensesDir {
@@ -27,8 +43,374 @@ pub(crate) enum Commands {
         /// name of the BOM file in each directory
         #[clap(value_parser, long, short = 'b')]
         bom_file: String,
-        /// path to the JSON configuration (allow-list)
-        #[clap(value_parser, long, short = 'c')]
+        #[clap(flatten)]
+        config_options: ConfigOptions,
+        /// write the report to this file instead of stdout
+        #[clap(long, value_name = "PATH")]
+        output: Option<std::path::PathBuf>,
+        #[clap(flatten)]
+        dir_options: DirOptions,
+        #[clap(flatten)]
+        bom_options: BomOptions,
+        #[clap(flatten)]
+        options: ReportOptions,
+    },
+    /// outputs a report for every target of the configuration, each from the BOM in the directory named after it
+    GenAll {
+        /// directory containing a directory for each target
+        #[clap(value_parser, long, short = 'l')]
+        list_dir: std::path::PathBuf,
+        /// name of the BOM file in each directory
+        #[clap(value_parser, long, short = 'b')]
+        bom_file: String,
+        #[clap(flatten)]
+        config_options: ConfigOptions,
+        /// directory the reports are written to, one file per target
+        #[clap(value_parser, long, short = 'o')]
+        output_dir: std::path::PathBuf,
+        #[clap(flatten)]
+        bom_options: BomOptions,
+        #[clap(flatten)]
+        options: ReportOptions,
+    },
+    /// outputs a skeleton JSON configuration (allow-list) for every component in a BOM
+    GenConfigFromBom {
+        /// path to the cyclonedx BOM, or - to read it from stdin
+        #[clap(value_parser, long, short = 'b')]
+        bom_path: std::path::PathBuf,
+        /// keep the entries of this JSON configuration and only add the components it does not list
+        #[clap(long, value_name = "PATH")]
+        merge: Option<std::path::PathBuf>,
+    },
+    /// outputs known incompatibilities between the dependency licenses and the product license
+    CheckCompat {
+        /// path to the cyclonedx BOM, or - to read it from stdin
+        #[clap(value_parser, long, short = 'b')]
+        bom_path: std::path::PathBuf,
+        #[clap(flatten)]
+        config_options: ConfigOptions,
+        /// SPDX id of the license the product is distributed under
+        #[clap(long)]
+        product_license: String,
+        #[clap(flatten)]
+        bom_options: BomOptions,
+    },
+    /// outputs the name and version of every distributed 3rd party crate
+    ListCrates {
+        /// path to the cyclonedx BOM, or - to read it from stdin
+        #[clap(value_parser, long, short = 'b')]
+        bom_path: std::path::PathBuf,
+        #[clap(flatten)]
+        config_options: ConfigOptions,
+        /// output a JSON array instead of lines
+        #[clap(long)]
+        json: bool,
+        #[clap(flatten)]
+        bom_options: BomOptions,
+    },
+    /// checks that a report could be generated for a BOM without producing it
+    CheckBom {
+        /// path to the cyclonedx BOM, or - to read it from stdin
+        #[clap(value_parser, long, short = 'b')]
+        bom_path: std::path::PathBuf,
+        #[clap(flatten)]
+        config_options: ConfigOptions,
+        #[clap(flatten)]
+        bom_options: BomOptions,
+        #[clap(flatten)]
+        checks: CheckOptions,
+    },
+    /// warns about every entry of the configuration that does not match a component of the BOM
+    LintConfig {
+        /// path to the cyclonedx BOM, or - to read it from stdin
+        #[clap(value_parser, long, short = 'b')]
+        bom_path: std::path::PathBuf,
+        #[clap(flatten)]
+        config_options: ConfigOptions,
+        /// exit with an error if any entry is unused
+        #[clap(long)]
+        strict: bool,
+        #[clap(flatten)]
+        bom_options: BomOptions,
+    },
+    /// warns about components whose license in the BOM differs from the one in the configuration
+    Reconcile {
+        /// path to the cyclonedx BOM, or - to read it from stdin
+        #[clap(value_parser, long, short = 'b')]
+        bom_path: std::path::PathBuf,
+        #[clap(flatten)]
+        config_options: ConfigOptions,
+        /// exit with an error if any license differs
+        #[clap(long)]
+        strict: bool,
+        #[clap(flatten)]
+        bom_options: BomOptions,
+    },
+    /// outputs a CycloneDX 1.4 JSON BOM with the components of several BOMs, without duplicates
+    MergeBoms {
+        /// path to a cyclonedx BOM (may be repeated)
+        #[clap(value_parser, long = "bom", short = 'b', required = true)]
+        bom_paths: Vec<std::path::PathBuf>,
+        #[clap(flatten)]
+        bom_options: BomOptions,
+    },
+    /// outputs why a crate is or is not included in the report
+    Explain {
+        /// path to the cyclonedx BOM, or - to read it from stdin
+        #[clap(value_parser, long, short = 'b')]
+        bom_path: std::path::PathBuf,
+        #[clap(flatten)]
+        config_options: ConfigOptions,
+        /// name of the crate to explain
+        #[clap(value_parser)]
+        crate_name: String,
+        #[clap(flatten)]
+        bom_options: BomOptions,
+        #[clap(flatten)]
+        checks: CheckOptions,
+    },
+    /// outputs a summary of the metadata in a BOM
+    BomInfo {
+        /// path to the cyclonedx BOM, or - to read it from stdin
+        #[clap(value_parser)]
+        bom_path: std::path::PathBuf,
+    },
+    /// outputs the semantic differences between two JSON configurations (allow-lists)
+    DiffConfig {
+        /// path to the old JSON configuration
+        #[clap(value_parser)]
+        old_path: std::path::PathBuf,
+        /// path to the new JSON configuration
+        #[clap(value_parser)]
+        new_path: std::path::PathBuf,
+        /// exit with an error if the configurations differ
+        #[clap(long)]
+        check: bool,
+    },
+    /// outputs the crates added, removed or changed between two Cargo.lock files and fails if an added crate is not in the configuration
+    LockfileDiff {
+        /// path to the old Cargo.lock
+        #[clap(value_parser)]
+        old_path: std::path::PathBuf,
+        /// path to the new Cargo.lock
+        #[clap(value_parser)]
+        new_path: std::path::PathBuf,
+        #[clap(flatten)]
+        config_options: ConfigOptions,
+    },
+    /// outputs a JSON Schema of the JSON configuration (allow-list) for editor validation
+    ConfigSchema,
+    /// rewrites a JSON configuration (allow-list) in place with canonical formatting and key order
+    FmtConfig {
+        /// path to the JSON configuration
+        #[clap(value_parser)]
         config_path: std::path::PathBuf,
+        /// exit with an error instead of rewriting the file if it is not formatted
+        #[clap(long)]
+        check: bool,
     },
 }
+
+/// options that select the configuration (allow-list)
+#[derive(Args)]
+pub(crate) struct ConfigOptions {
+    /// path to the JSON configuration (allow-list), may be repeated to merge several with later ones overriding earlier ones
+    #[clap(value_parser, long, short = 'c', required = true)]
+    pub(crate) config_path: Vec<std::path::PathBuf>,
+    /// allow a configuration to override a third_party entry of an earlier one with other licenses
+    #[clap(long)]
+    pub(crate) force: bool,
+}
+
+impl ConfigOptions {
+    /// Load and merge the configurations
+    pub(crate) fn load(&self) -> Result<Config, anyhow::Error> {
+        Config::load_all(&self.config_path, self.force)
+    }
+
+    /// Paths of the configurations as they are printed in messages
+    pub(crate) fn describe(&self) -> String {
+        let paths: Vec<String> = self
+            .config_path
+            .iter()
+            .map(|x| x.display().to_string())
+            .collect();
+        paths.join(", ")
+    }
+}
+
+/// options that control how components in a BOM are matched against the configuration
+#[derive(Args, Default)]
+pub(crate) struct BomOptions {
+    /// match crate names against the configuration ignoring case, warning when this was needed
+    #[clap(long)]
+    pub(crate) ignore_case: bool,
+    /// warn about crates listed in build_only that the BOM does not scope as build-only, i.e. that also have a runtime edge
+    #[clap(long)]
+    pub(crate) include_transitive_build_deps: bool,
+    /// fail unless every component has a cargo purl whose name and version agree with the component
+    #[clap(long)]
+    pub(crate) validate_purls: bool,
+    /// fail instead of inferring names or versions from purls, matching names ignoring case or using the default copyright placeholder
+    #[clap(long, conflicts_with = "ignore_case")]
+    pub(crate) frozen: bool,
+}
+
+/// options that control how the BOMs in a directory listing are merged
+#[derive(Args)]
+pub(crate) struct DirOptions {
+    /// fail listing every directory that does not contain the BOM file
+    #[clap(long)]
+    pub(crate) require_all: bool,
+    /// annotate each crate with the directories (targets) it was found in, or "all"
+    #[clap(long)]
+    pub(crate) show_targets: bool,
+    /// annotate each crate with its versions in each directory (target), allowing the versions to differ
+    #[clap(long)]
+    pub(crate) show_target_versions: bool,
+    /// warn about every crate whose versions span more than one semver-incompatible series,
+    /// merging the versions that differ between directories instead of failing
+    #[clap(long)]
+    pub(crate) warn_version_skew: bool,
+    /// with --warn-version-skew, also warn about every crate with more than this many versions
+    #[clap(long, value_name = "COUNT", requires = "warn_version_skew")]
+    pub(crate) max_versions: Option<usize>,
+}
+
+/// checks that must pass before a report is generated
+#[derive(Args)]
+pub(crate) struct CheckOptions {
+    /// fail if any distributed crate is a pre-release version
+    #[clap(long)]
+    pub(crate) deny_pre_release: bool,
+    /// fail if any distributed crate was built from a git repository instead of a registry
+    #[clap(long)]
+    pub(crate) deny_git_deps: bool,
+    /// fail if any distributed crate does not resolve to a usable URL
+    #[clap(long)]
+    pub(crate) require_urls: bool,
+    /// fail if any distributed crate has a license at least as restrictive as this, or of unknown category
+    #[clap(long, value_enum, value_name = "LEVEL", default_value_t = Severity::None)]
+    pub(crate) min_severity: Severity,
+    /// fail if any crate would be printed with the placeholder for a missing copyright statement
+    #[clap(long)]
+    pub(crate) pedantic: bool,
+    /// accept the licenses declared in the BOM for crates that are not in the configuration, with a warning
+    #[clap(long, conflicts_with = "frozen")]
+    pub(crate) allow_bom_licenses: bool,
+}
+
+/// options that control how the license report is rendered
+#[derive(Args)]
+pub(crate) struct ReportOptions {
+    #[clap(flatten)]
+    pub(crate) checks: CheckOptions,
+    /// show how many crates use each license in the list at the top of the report
+    #[clap(long)]
+    pub(crate) license_counts: bool,
+    /// annotate each license at the top of the report with its key obligations (patent grant, copyleft, attribution)
+    #[clap(long)]
+    pub(crate) show_obligations: bool,
+    /// collapse the versions of each crate into one range per major.minor series
+    #[clap(long)]
+    pub(crate) collapse_versions: bool,
+    /// canonicalize the copyright symbol and collapse lists of years into ranges
+    #[clap(long)]
+    pub(crate) normalize_copyright: bool,
+    /// SPDX id of a license whose full text is left out of the end of the report (may be repeated)
+    #[clap(long = "omit-text", value_name = "SPDX")]
+    pub(crate) omit_text: Vec<String>,
+    /// leave out the crate versions that are also in this BOM, e.g. the one of the host application
+    #[clap(long, value_name = "BOM")]
+    pub(crate) baseline: Option<std::path::PathBuf>,
+    /// only report the versions of CRATE newer than VERSION, e.g. the last audited one (may be repeated)
+    #[clap(long, value_name = "CRATE@VERSION", value_parser = parse_since_version)]
+    pub(crate) since_version: Vec<(String, semver::Version)>,
+    /// word-wrap prose and copyright lines at this many columns, leaving license texts untouched
+    #[clap(long, value_name = "COLS")]
+    pub(crate) wrap: Option<usize>,
+    /// write the header and per-crate information to this file instead of stdout
+    #[clap(long, value_name = "PATH", requires = "text_output")]
+    pub(crate) summary_output: Option<std::path::PathBuf>,
+    /// write the full license texts to this file instead of the end of the report
+    #[clap(long, value_name = "PATH", requires = "summary_output")]
+    pub(crate) text_output: Option<std::path::PathBuf>,
+    /// write each license text to a file in this directory and reference it from the report
+    #[clap(long, value_name = "DIR")]
+    pub(crate) license_dir: Option<std::path::PathBuf>,
+    /// write the copyright and attribution lines of every crate to this file instead of the report
+    #[clap(long, value_name = "PATH")]
+    pub(crate) attributions: Option<std::path::PathBuf>,
+    /// also write one row per crate (versions, licenses, source, copyright present) to this CSV file
+    #[clap(long, value_name = "PATH")]
+    pub(crate) csv: Option<std::path::PathBuf>,
+    /// format of the report
+    #[clap(long, value_enum, default_value_t = Format::Text)]
+    pub(crate) format: Format,
+    /// character encoding of the report and the attributions file
+    #[clap(long, value_enum, default_value_t = Encoding::Utf8)]
+    pub(crate) output_encoding: Encoding,
+    /// order of the crates in the report
+    #[clap(long, value_enum, default_value_t = SortBy::Name)]
+    pub(crate) sort_by: SortBy,
+    /// issue HEAD requests for every URL in the report and warn about any that are unreachable
+    #[cfg(feature = "network")]
+    #[clap(long)]
+    pub(crate) check_urls: bool,
+}
+
+/// least restrictive license category that fails the checks
+#[derive(ValueEnum, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum Severity {
+    /// never fail because of the license category
+    None,
+    /// fail on weak copyleft (e.g. MPL), copyleft and network copyleft licenses
+    WeakCopyleft,
+    /// fail on copyleft (e.g. GPL) and network copyleft licenses
+    Copyleft,
+    /// fail only on network copyleft licenses (e.g. AGPL)
+    NetworkCopyleft,
+}
+
+/// format of the license report
+#[derive(ValueEnum, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum Format {
+    /// plain text
+    Text,
+    /// reStructuredText, e.g. for Sphinx
+    Rst,
+    /// HTML fragment, e.g. for an "About" dialog
+    Html,
+    /// JSON document listing the crates and licenses, without the license texts
+    Json,
+}
+
+/// Parse a `CRATE@VERSION` pair given to `--since-version`
+fn parse_since_version(value: &str) -> Result<(String, semver::Version), String> {
+    let (name, version) = value
+        .split_once('@')
+        .ok_or_else(|| format!("expected CRATE@VERSION but found '{value}'"))?;
+    let version = semver::Version::parse(version)
+        .map_err(|err| format!("invalid version '{version}': {err}"))?;
+    Ok((name.to_string(), version))
+}
+
+/// character encoding of the license report
+#[derive(ValueEnum, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum Encoding {
+    /// UTF-8
+    Utf8,
+    /// UTF-8 starting with a byte order mark, e.g. for older Windows tools
+    Utf8Bom,
+    /// ISO-8859-1, transliterating or replacing the characters it cannot represent
+    Latin1,
+}
+
+/// order of the crates in the report
+#[derive(ValueEnum, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum SortBy {
+    /// alphabetically by crate name
+    Name,
+    /// network copyleft, copyleft, weak copyleft, then permissive, each alphabetically by name
+    Risk,
+}
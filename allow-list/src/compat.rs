@@ -0,0 +1,540 @@
+use crate::cli::{BomOptions, ConfigOptions, Severity};
+use crate::config::{Combine, Package};
+use crate::licenses::{extract_deps, read_bom};
+use std::collections::BTreeSet;
+use std::path::Path;
+
+/// Broad category of a license, ordered from least to most restrictive
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Category {
+    /// Only requires attribution and/or preservation of the license text
+    Permissive,
+    /// Requires modifications to the licensed files to be shared
+    WeakCopyleft,
+    /// Requires the combined work to be distributed under the same license
+    Copyleft,
+    /// Copyleft that also applies to software offered over a network
+    NetworkCopyleft,
+}
+
+impl Category {
+    /// Name of the category as it appears in messages
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Category::Permissive => "permissive",
+            Category::WeakCopyleft => "weak copyleft",
+            Category::Copyleft => "copyleft",
+            Category::NetworkCopyleft => "network copyleft",
+        }
+    }
+}
+
+/// Category of each known SPDX id
+const CATEGORIES: &[(&str, Category)] = &[
+    ("0BSD", Category::Permissive),
+    ("Apache-2.0", Category::Permissive),
+    ("BSD-2-Clause", Category::Permissive),
+    ("BSD-3-Clause", Category::Permissive),
+    ("BSL-1.0", Category::Permissive),
+    ("ISC", Category::Permissive),
+    ("MIT", Category::Permissive),
+    ("OpenSSL", Category::Permissive),
+    ("Unicode-DFS-2016", Category::Permissive),
+    ("WTFPL", Category::Permissive),
+    ("Zlib", Category::Permissive),
+    ("LGPL-2.1-only", Category::WeakCopyleft),
+    ("LGPL-2.1-or-later", Category::WeakCopyleft),
+    ("LGPL-3.0-only", Category::WeakCopyleft),
+    ("LGPL-3.0-or-later", Category::WeakCopyleft),
+    ("MPL-1.1", Category::WeakCopyleft),
+    ("MPL-2.0", Category::WeakCopyleft),
+    ("EUPL-1.2", Category::Copyleft),
+    ("GPL-2.0-only", Category::Copyleft),
+    ("GPL-2.0-or-later", Category::Copyleft),
+    ("GPL-3.0-only", Category::Copyleft),
+    ("GPL-3.0-or-later", Category::Copyleft),
+    ("AGPL-3.0-only", Category::NetworkCopyleft),
+    ("AGPL-3.0-or-later", Category::NetworkCopyleft),
+];
+
+/// Ids of modified licenses that carry the obligations of the SPDX license they extend
+const DERIVED: &[(&str, &str)] = &[("LicenseRef-BSD-3-Clause-Extra", "BSD-3-Clause")];
+
+/// SPDX ids of the licenses that grant the licensee the patents of the contributors
+const PATENT_GRANTS: &[&str] = &[
+    "AGPL-3.0-only",
+    "AGPL-3.0-or-later",
+    "Apache-2.0",
+    "EUPL-1.2",
+    "GPL-3.0-only",
+    "GPL-3.0-or-later",
+    "LGPL-3.0-only",
+    "LGPL-3.0-or-later",
+    "MPL-2.0",
+];
+
+/// SPDX ids of the permissive licenses whose notice must accompany binary distributions
+const ATTRIBUTION: &[&str] = &[
+    "Apache-2.0",
+    "BSD-2-Clause",
+    "BSD-3-Clause",
+    "ISC",
+    "MIT",
+    "OpenSSL",
+    "Unicode-DFS-2016",
+];
+
+/// Pairs of licenses that cannot be combined in a single distributed work
+const INCOMPATIBLE: &[(&str, &str)] = &[
+    ("Apache-2.0", "GPL-2.0-only"),
+    ("OpenSSL", "GPL-2.0-only"),
+    ("OpenSSL", "GPL-2.0-or-later"),
+    ("OpenSSL", "GPL-3.0-only"),
+    ("OpenSSL", "GPL-3.0-or-later"),
+    ("MPL-1.1", "GPL-2.0-only"),
+    ("MPL-1.1", "GPL-2.0-or-later"),
+    ("MPL-1.1", "GPL-3.0-only"),
+    ("MPL-1.1", "GPL-3.0-or-later"),
+    ("GPL-2.0-only", "GPL-3.0-only"),
+    ("GPL-2.0-only", "GPL-3.0-or-later"),
+    ("GPL-2.0-only", "LGPL-3.0-only"),
+    ("GPL-2.0-only", "LGPL-3.0-or-later"),
+    ("GPL-2.0-only", "AGPL-3.0-only"),
+    ("GPL-2.0-only", "AGPL-3.0-or-later"),
+];
+
+/// Look up the category of an SPDX id, if it is known
+///
+/// An exception (`WITH ...`) only grants additional permissions, so the license is categorized without it
+pub(crate) fn category(spdx: &str) -> Option<Category> {
+    let spdx = base(spdx);
+    CATEGORIES
+        .iter()
+        .find(|(id, _)| *id == spdx)
+        .map(|(_, category)| *category)
+}
+
+/// Key obligations of a license, as an advisory summary for reviewers, or `None` if the license is unknown
+pub(crate) fn obligations(spdx: &str) -> Option<Vec<&'static str>> {
+    let category = category(spdx)?;
+    let spdx = base(spdx);
+    let mut obligations = Vec::new();
+    if PATENT_GRANTS.contains(&spdx) {
+        obligations.push("patent grant");
+    }
+    match category {
+        Category::Permissive if ATTRIBUTION.contains(&spdx) => {
+            obligations.push("attribution required")
+        }
+        Category::Permissive => {}
+        category => obligations.push(category.name()),
+    }
+    Some(obligations)
+}
+
+/// The SPDX id of a license without any `WITH` exception
+pub(crate) fn without_exception(spdx: &str) -> &str {
+    spdx.split(" WITH ").next().unwrap_or(spdx)
+}
+
+/// The SPDX license whose obligations apply, i.e. without any `WITH` exception
+/// and with the license that a modified license extends
+fn base(spdx: &str) -> &str {
+    let spdx = without_exception(spdx);
+    DERIVED
+        .iter()
+        .find(|(id, _)| *id == spdx)
+        .map_or(spdx, |(_, base)| base)
+}
+
+/// Risk of distributing a package, used to rank crates in the report
+///
+/// The risk is the most restrictive category among AND licenses, or the least restrictive
+/// among OR licenses since any one of them may be chosen. Licenses without a known category
+/// rank as copyleft so that they are reviewed early.
+pub(crate) fn risk(pkg: &Package) -> Category {
+    risk_with(pkg, Category::Copyleft)
+}
+
+/// The risk of a package, ranking the licenses without a known category as `unknown`
+fn risk_with(pkg: &Package, unknown: Category) -> Category {
+    pkg.expression().fold(
+        &mut |id| category(id).unwrap_or(unknown),
+        &|combine, categories| {
+            let risk = match combine {
+                Combine::And => categories.into_iter().max(),
+                Combine::Or => categories.into_iter().min(),
+            };
+            risk.unwrap_or(unknown)
+        },
+    )
+}
+
+/// Why a package is flagged by a severity threshold
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum Flagged {
+    /// the licenses of the package are at least as restrictive as the threshold
+    Category(Category),
+    /// the package may reach the threshold depending on these licenses without a known category
+    Unknown(Vec<String>),
+}
+
+/// Whether a package is at least as restrictive as the `threshold`, or may be given the licenses
+/// that have no known category
+pub(crate) fn at_or_above(pkg: &Package, threshold: Severity) -> Option<Flagged> {
+    let threshold = match threshold {
+        Severity::None => return None,
+        Severity::WeakCopyleft => Category::WeakCopyleft,
+        Severity::Copyleft => Category::Copyleft,
+        Severity::NetworkCopyleft => Category::NetworkCopyleft,
+    };
+    let known = risk_with(pkg, Category::Permissive);
+    if known >= threshold {
+        return Some(Flagged::Category(known));
+    }
+    if risk_with(pkg, Category::NetworkCopyleft) < threshold {
+        return None;
+    }
+    let expression = pkg.expression();
+    let unknown = expression
+        .ids()
+        .into_iter()
+        .filter(|x| category(x).is_none())
+        .map(|x| x.to_string())
+        .collect();
+    Some(Flagged::Unknown(unknown))
+}
+
+fn is_incompatible(a: &str, b: &str) -> bool {
+    let (a, b) = (base(a), base(b));
+    INCOMPATIBLE
+        .iter()
+        .any(|(x, y)| (*x == a && *y == b) || (*x == b && *y == a))
+}
+
+/// Name of the license without any `WITH` exception and the '-only' / '-or-later' suffix
+fn family(spdx: &str) -> &str {
+    let spdx = without_exception(spdx);
+    spdx.strip_suffix("-only")
+        .or_else(|| spdx.strip_suffix("-or-later"))
+        .unwrap_or(spdx)
+}
+
+/// Problems with distributing a dependency license as part of a product under `product`
+fn problems(dep: &str, product: &str) -> Vec<String> {
+    let mut problems = Vec::new();
+    if let Some(Category::Copyleft | Category::NetworkCopyleft) = category(dep) {
+        if family(dep) != family(product) {
+            problems.push(format!(
+                "{dep} is copyleft and requires the product to be licensed under {}",
+                without_exception(dep)
+            ));
+        }
+    }
+    if is_incompatible(dep, product) {
+        problems.push(format!("{dep} is incompatible with {product}"));
+    }
+    problems
+}
+
+/// Print any known incompatibilities between the dependency licenses and the product license
+///
+/// This is advisory and does not replace a legal review
+pub(crate) fn check_compat<W>(
+    bom_path: &Path,
+    config_options: &ConfigOptions,
+    product_license: &str,
+    bom_options: &BomOptions,
+    mut w: W,
+) -> Result<(), anyhow::Error>
+where
+    W: std::io::Write,
+{
+    let config = config_options.load()?;
+    let components = extract_deps(read_bom(bom_path, bom_options)?, &config, bom_options)?;
+
+    let mut findings = Vec::new();
+    // licenses that must be fulfilled and therefore end up combined in the product
+    let mut required: BTreeSet<String> = BTreeSet::new();
+
+    for name in components.keys() {
+        let pkg = config.third_party.get(name).ok_or_else(|| {
+            anyhow::Error::msg(format!("3rd party package {name} not in the allow list"))
+        })?;
+
+        let expression = pkg.expression();
+
+        let mut per_license: Vec<Vec<String>> = Vec::new();
+        let flagged = expression.fold(
+            &mut |id| {
+                let problems = problems(id, product_license);
+                let flagged = !problems.is_empty();
+                per_license.push(problems);
+                flagged
+            },
+            &|combine, flagged| match combine {
+                Combine::And => flagged.into_iter().any(|x| x),
+                // any single alternative that works for the product is sufficient
+                Combine::Or => flagged.into_iter().all(|x| x),
+            },
+        );
+
+        if flagged {
+            for problem in per_license.into_iter().flatten() {
+                findings.push(format!("{name}: {problem}"));
+            }
+        }
+
+        // only the licenses present in every alternative are certain to be combined
+        let must: BTreeSet<String> = expression.fold(
+            &mut |id| BTreeSet::from([id.to_string()]),
+            &|combine, sets| match combine {
+                Combine::And => sets.into_iter().flatten().collect(),
+                Combine::Or => sets
+                    .into_iter()
+                    .reduce(|a, b| a.intersection(&b).cloned().collect())
+                    .unwrap_or_default(),
+            },
+        );
+        required.extend(must);
+    }
+
+    for a in required.iter() {
+        for b in required.iter().filter(|b| a < *b) {
+            if is_incompatible(a, b) {
+                findings.push(format!(
+                    "{a} and {b} are both required by dependencies but are incompatible"
+                ));
+            }
+        }
+    }
+
+    if findings.is_empty() {
+        writeln!(
+            w,
+            "No known incompatibilities with a product licensed under {product_license}"
+        )?;
+    } else {
+        for finding in findings {
+            writeln!(w, "{finding}")?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{bom, config_options, TempDir, CONFIG};
+
+    fn check(config: &str, bom: &str, product: &str) -> String {
+        let dir = TempDir::new("check-compat");
+        let config = dir.write("config.json", config);
+        let bom = dir.write("bom.json", bom);
+
+        let mut out = Vec::new();
+        check_compat(
+            &bom,
+            &config_options(&[&config]),
+            product,
+            &BomOptions::default(),
+            &mut out,
+        )
+        .unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn openssl_dependency_in_a_gpl2_product_is_flagged() {
+        let bom = bom(&[("serde", "1.0.188"), ("ring", "0.17.5")]);
+
+        assert_eq!(
+            check(CONFIG, &bom, "GPL-2.0-only"),
+            "ring: OpenSSL is incompatible with GPL-2.0-only\n"
+        );
+        assert_eq!(
+            check(CONFIG, &bom, "Apache-2.0"),
+            "No known incompatibilities with a product licensed under Apache-2.0\n"
+        );
+    }
+
+    #[test]
+    fn incompatible_alternative_is_not_flagged() {
+        let config = CONFIG.replace(r#""id": "ring","#, r#""id": "ring", "combine": "OR","#);
+
+        assert_eq!(
+            check(&config, &bom(&[("ring", "0.17.5")]), "GPL-2.0-only"),
+            "No known incompatibilities with a product licensed under GPL-2.0-only\n"
+        );
+    }
+
+    #[test]
+    fn gpl_dependency_in_a_permissive_product_is_flagged() {
+        let config = CONFIG.replace(
+            r#"[{"MIT": {"copyright": "NotPresent"}}]"#,
+            r#"[{"GPLv3": {}}]"#,
+        );
+        let bom = bom(&[("serde", "1.0.188"), ("libc", "0.2.150")]);
+
+        assert_eq!(
+            check(&config, &bom, "Apache-2.0"),
+            "libc: GPL-3.0-only is copyleft and requires the product to be licensed under GPL-3.0-only\n"
+        );
+        assert_eq!(
+            check(&config, &bom, "GPL-3.0-only"),
+            "No known incompatibilities with a product licensed under GPL-3.0-only\n"
+        );
+    }
+
+    #[test]
+    fn gpl_alternative_is_not_flagged() {
+        let config = CONFIG.replace(
+            r#""licenses": [{"MIT": {"copyright": "NotPresent"}}]"#,
+            r#""combine": "OR", "licenses": [{"GPLv3": {}}, {"MIT": {"copyright": "NotPresent"}}]"#,
+        );
+
+        assert_eq!(
+            check(&config, &bom(&[("libc", "0.2.150")]), "Apache-2.0"),
+            "No known incompatibilities with a product licensed under Apache-2.0\n"
+        );
+    }
+
+    fn package(licenses: &str) -> Package {
+        serde_json::from_str(&format!(
+            r#"{{"id":"foo","source":"crates.io","licenses":{licenses}}}"#
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn min_severity_fails_at_and_above_the_threshold() {
+        let permissive = package(r#"[{"MIT":{"copyright":"NotPresent"}}]"#);
+        let weak = package(r#"["MPLv2"]"#);
+        let copyleft = package(r#"[{"GPLv3":{}}]"#);
+        let network = package(
+            r#"[{"Custom":{"spdx":"AGPL-3.0-only","url":"https://spdx.org/licenses/AGPL-3.0-only.html","text":"AGPL","copyright":"NotPresent"}}]"#,
+        );
+        let packages = [&permissive, &weak, &copyleft, &network];
+
+        let flagged = |threshold| {
+            packages
+                .iter()
+                .map(|x| at_or_above(x, threshold).is_some())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(flagged(Severity::None), [false, false, false, false]);
+        assert_eq!(flagged(Severity::WeakCopyleft), [false, true, true, true]);
+        assert_eq!(flagged(Severity::Copyleft), [false, false, true, true]);
+        assert_eq!(
+            flagged(Severity::NetworkCopyleft),
+            [false, false, false, true]
+        );
+        assert_eq!(
+            at_or_above(&copyleft, Severity::WeakCopyleft),
+            Some(Flagged::Category(Category::Copyleft))
+        );
+    }
+
+    #[test]
+    fn min_severity_flags_licenses_of_unknown_category_as_such() {
+        let custom = r#"{"Custom":{"spdx":"LicenseRef-Foo","url":"https://example.com/foo","text":"Foo","copyright":"NotPresent"}}"#;
+        let unknown = package(&format!("[{custom}]"));
+
+        assert_eq!(at_or_above(&unknown, Severity::None), None);
+        assert_eq!(
+            at_or_above(&unknown, Severity::WeakCopyleft),
+            Some(Flagged::Unknown(vec!["LicenseRef-Foo".to_string()]))
+        );
+        // a known category above the threshold is reported as such
+        let gpl = package(&format!(r#"[{{"GPLv3":{{}}}}, {custom}]"#));
+        assert_eq!(
+            at_or_above(&gpl, Severity::Copyleft),
+            Some(Flagged::Category(Category::Copyleft))
+        );
+        // a permissive alternative keeps the package below any threshold
+        let mut mit = package(&format!(
+            r#"[{{"MIT":{{"copyright":"NotPresent"}}}}, {custom}]"#
+        ));
+        mit.combine = Combine::Or;
+        assert_eq!(at_or_above(&mit, Severity::WeakCopyleft), None);
+    }
+
+    #[test]
+    fn obligations_come_from_the_static_tables() {
+        assert_eq!(
+            obligations("Apache-2.0"),
+            Some(vec!["patent grant", "attribution required"])
+        );
+        assert_eq!(
+            obligations("Apache-2.0 WITH LLVM-exception"),
+            Some(vec!["patent grant", "attribution required"])
+        );
+        assert_eq!(obligations("MIT"), Some(vec!["attribution required"]));
+        assert_eq!(
+            obligations("MPL-2.0"),
+            Some(vec!["patent grant", "weak copyleft"])
+        );
+        assert_eq!(obligations("GPL-2.0-only"), Some(vec!["copyleft"]));
+        assert_eq!(obligations("0BSD"), Some(vec![]));
+        assert_eq!(obligations("LicenseRef-unknown"), None);
+    }
+
+    #[test]
+    fn family_ignores_the_version_suffix_and_exception() {
+        assert_eq!(family("GPL-3.0-only"), "GPL-3.0");
+        assert_eq!(family("GPL-3.0-or-later"), "GPL-3.0");
+        assert_eq!(family("GPL-3.0-only WITH GCC-exception-3.1"), "GPL-3.0");
+        assert_eq!(family("MIT"), "MIT");
+    }
+
+    #[test]
+    fn copyleft_with_exception_is_compatible_with_the_same_license() {
+        assert!(problems("GPL-3.0-only WITH GCC-exception-3.1", "GPL-3.0-only").is_empty());
+        assert!(problems("GPL-3.0-or-later WITH GCC-exception-3.1", "GPL-3.0-only").is_empty());
+    }
+
+    #[test]
+    fn copyleft_requires_the_product_license() {
+        assert_eq!(
+            problems("GPL-3.0-only WITH GCC-exception-3.1", "MIT"),
+            ["GPL-3.0-only WITH GCC-exception-3.1 is copyleft and requires the product to be licensed under GPL-3.0-only"]
+        );
+        assert_eq!(
+            problems("GPL-3.0-only", "GPL-2.0-only"),
+            [
+                "GPL-3.0-only is copyleft and requires the product to be licensed under GPL-3.0-only",
+                "GPL-3.0-only is incompatible with GPL-2.0-only",
+            ]
+        );
+        assert!(problems("MIT", "GPL-2.0-only").is_empty());
+    }
+
+    #[test]
+    fn exceptions_do_not_change_the_category() {
+        assert_eq!(
+            category("GPL-3.0-or-later WITH GCC-exception-3.1"),
+            Some(Category::Copyleft)
+        );
+        assert_eq!(category("LicenseRef-unknown"), None);
+    }
+
+    #[test]
+    fn bsd3_with_an_extra_clause_has_the_obligations_of_bsd3() {
+        let pkg: Package = serde_json::from_str(
+            r#"{"id":"serde","source":"crates.io","licenses":[{"BSD3":{"copyright":"NotPresent","extra_clause":"No nuclear facilities."}}]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            pkg.expression().to_string(),
+            "LicenseRef-BSD-3-Clause-Extra"
+        );
+
+        assert_eq!(risk(&pkg), Category::Permissive);
+        assert_eq!(at_or_above(&pkg, Severity::Copyleft), None);
+        assert_eq!(
+            obligations("LicenseRef-BSD-3-Clause-Extra"),
+            Some(vec!["attribution required"])
+        );
+    }
+}
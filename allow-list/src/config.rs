@@ -1,9 +1,13 @@
+use std::borrow::Cow;
 use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
 
+use crate::expression::Expression;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 /// A copyright statement associated with a license
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, PartialEq, Eq)]
 pub(crate) enum Copyright {
     /// Copyright statement is present in the license file that consists of one of more lines
     Lines(Vec<String>),
@@ -11,33 +15,48 @@ pub(crate) enum Copyright {
     NotPresent,
 }
 
+/// Printed in place of a missing copyright statement unless the configuration overrides it
+const DEFAULT_COPYRIGHT_PLACEHOLDER: &str =
+    "No copyright statement was provided by the author even though the license may refer to it";
+
 impl Copyright {
-    fn lines(&self) -> Vec<String> {
+    fn lines(&self, placeholder: &str) -> Vec<String> {
         match self {
             Copyright::Lines(x) => x.clone(),
-            Copyright::NotPresent => vec!["No copyright statement was provided by the author even though they license may refer to it".to_string()],
+            Copyright::NotPresent => vec![placeholder.to_string()],
         }
     }
 }
 
 /// Where information about the crate can be found
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, PartialEq, Eq)]
 pub(crate) enum Source {
     /// This crate came from crates.io
     #[serde(rename = "crates.io")]
     CratesIo,
+    /// This crate came from a git repository
+    #[serde(rename = "git")]
+    Git {
+        /// URL of the repository
+        repo: String,
+        /// revision (commit, tag or branch) the crate was built from
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        rev: Option<String>,
+    },
 }
 
 /// Information about a license
-pub(crate) struct LicenseInfo {
+pub(crate) struct LicenseInfo<'a> {
     /// URL of the license
-    pub(crate) url: &'static str,
+    pub(crate) url: &'a str,
     /// Text of the license
-    pub(crate) text: &'static str,
+    pub(crate) text: Cow<'a, str>,
+    /// Optional remark about the license printed alongside its URL
+    pub(crate) note: Option<&'static str>,
 }
 
 /// License type
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, PartialEq, Eq)]
 pub(crate) enum License {
     Unknown,
     #[serde(rename = "ISC")]
@@ -54,6 +73,9 @@ pub(crate) enum License {
     /// Boost software license v1 - <https://www.boost.org/users/license.html>
     #[serde(rename = "BSLv1")]
     Bsl1,
+    /// MPL Version 1.1 - <https://www.mozilla.org/en-US/MPL/1.1/>
+    #[serde(rename = "MPLv1.1")]
+    Mpl1,
     /// MPL Version 2.0 - <https://www.mozilla.org/en-US/MPL/2.0/>
     #[serde(rename = "MPLv2")]
     Mpl2,
@@ -61,14 +83,95 @@ pub(crate) enum License {
     #[serde(rename = "BSD3")]
     Bsd3 {
         copyright: Copyright,
+        /// clause added by the author, e.g. a patent or nuclear disclaimer, printed after the license text
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        extra_clause: Option<String>,
+    },
+    /// 2-clause BSD - <https://opensource.org/licenses/BSD-2-Clause>
+    #[serde(rename = "BSD2")]
+    Bsd2 {
+        copyright: Copyright,
+    },
+    /// zlib License - <https://www.zlib.net/zlib_license.html>
+    Zlib {
+        copyright: Copyright,
     },
+    /// BSD Zero Clause License - <https://opensource.org/licenses/0BSD>
+    #[serde(rename = "0BSD")]
+    ZeroBsd,
     /// Unicode License Agreement - Data Files and Software (2016)
     #[serde(rename = "UnicodeDFS2016")]
     UnicodeDfs2016,
+    /// Do What The F*ck You Want To Public License - <http://www.wtfpl.net/>
+    #[serde(rename = "WTFPL")]
+    Wtfpl,
+    /// European Union Public Licence v1.2 - <https://joinup.ec.europa.eu/collection/eupl/eupl-text-eupl-12>
+    #[serde(rename = "EUPLv1.2")]
+    Eupl12,
+    /// Apache License 2.0 - <https://www.apache.org/licenses/LICENSE-2.0>
+    Apache2 {
+        /// content of the NOTICE file of the crate, which must be preserved
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        notice: Option<Vec<String>>,
+    },
+    /// A license that is not bundled with the tool, fully described in the configuration
+    Custom {
+        /// SPDX id (or LicenseRef) of the license
+        spdx: String,
+        /// URL with information about the license
+        url: String,
+        /// full text of the license
+        text: String,
+        copyright: Copyright,
+    },
+    /// GNU General Public License v3.0 - <https://www.gnu.org/licenses/gpl-3.0.html>
+    #[serde(rename = "GPLv3")]
+    Gpl3 {
+        /// true if any later version of the license may be used
+        #[serde(default)]
+        or_later: bool,
+        /// optional exception granting additional permissions
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        exception: Option<Gpl3Exception>,
+    },
+}
+
+/// Exception that grants additional permissions on top of the GPL v3.0
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum Gpl3Exception {
+    /// GCC Runtime Library exception 3.1 - <https://www.gnu.org/licenses/gcc-exception-3.1.html>
+    #[serde(rename = "GCC-exception-3.1")]
+    Gcc31,
+}
+
+/// How multiple licenses of a package combine
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum Combine {
+    /// All of the licenses apply
+    #[default]
+    #[serde(rename = "AND")]
+    And,
+    /// Any one of the licenses may be chosen
+    #[serde(rename = "OR")]
+    Or,
+}
+
+impl Combine {
+    /// Separator used when printing the license expression
+    pub(crate) fn separator(self) -> &'static str {
+        match self {
+            Combine::And => " AND ",
+            Combine::Or => " OR ",
+        }
+    }
+
+    fn is_and(&self) -> bool {
+        *self == Combine::And
+    }
 }
 
 /// Information about a dependency
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, PartialEq, Eq)]
 pub(crate) struct Package {
     /// id of the allowed package
     pub(crate) id: String,
@@ -76,25 +179,108 @@ pub(crate) struct Package {
     pub(crate) source: Source,
     /// license identification
     pub(crate) licenses: Vec<License>,
+    /// how the licenses combine, defaults to AND
+    #[serde(default, skip_serializing_if = "Combine::is_and")]
+    pub(crate) combine: Combine,
+    /// SPDX expression that mixes AND and OR groups of the licenses, e.g. `(MIT OR Apache-2.0) AND ISC`, instead of `combine`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) expression: Option<String>,
+    /// optional note from reviewers that is printed in the report
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) comment: Option<String>,
+    /// SPDX id of the license chosen among OR licenses, the only one printed in the report
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) chosen: Option<String>,
+    /// why the license was chosen, printed in the report
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) chosen_rationale: Option<String>,
+}
+
+impl Source {
+    /// Name of the source as it appears in the configuration
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            Source::CratesIo => "crates.io",
+            Source::Git { .. } => "git",
+        }
+    }
+
+    /// Git source described by the `vcs_url` of a purl, e.g. `git+https://github.com/org/repo#rev`
+    pub(crate) fn from_vcs_url(url: &str) -> Source {
+        let url = url.strip_prefix("git+").unwrap_or(url);
+        match url.split_once('#') {
+            Some((repo, rev)) => Source::Git {
+                repo: repo.to_string(),
+                rev: Some(rev.to_string()),
+            },
+            None => Source::Git {
+                repo: url.to_string(),
+                rev: None,
+            },
+        }
+    }
 }
 
 impl Package {
     pub(crate) fn url(&self) -> String {
-        match self.source {
+        match &self.source {
             Source::CratesIo => format!("https://crates.io/crates/{}", self.id),
+            Source::Git { repo, rev: None } => repo.clone(),
+            Source::Git {
+                repo,
+                rev: Some(rev),
+            } => format!("{repo}#{rev}"),
+        }
+    }
+
+    /// Licenses under which the package is distributed, i.e. only the chosen one if a choice was recorded
+    pub(crate) fn effective_licenses(&self) -> impl Iterator<Item = &License> {
+        self.licenses.iter().filter(move |x| match &self.chosen {
+            Some(chosen) => !matches!(x, License::Unknown) && x.spdx_short() == chosen,
+            None => true,
+        })
+    }
+
+    /// License expression under which the package is distributed, i.e. only the chosen license if a choice was recorded
+    pub(crate) fn expression(&self) -> Expression {
+        match &self.chosen {
+            Some(chosen) => Expression::License(chosen.clone()),
+            None => self.offered_expression(),
+        }
+    }
+
+    /// License expression under which the package is offered, ignoring any recorded choice
+    pub(crate) fn offered_expression(&self) -> Expression {
+        if let Some(expression) = &self.expression {
+            return Expression::parse(expression)
+                .expect("license expressions are validated when the configuration is loaded");
         }
+        let licenses = self
+            .licenses
+            .iter()
+            .filter(|x| !matches!(x, License::Unknown))
+            .map(|x| Expression::License(x.spdx_short().to_string()))
+            .collect();
+        Expression::combine(self.combine, licenses)
     }
 }
 
 /// Information about a vendor package
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, PartialEq, Eq)]
 pub(crate) struct VendorPackage {
     /// SCM URL where the package is located
     pub(crate) url: String,
 }
 
+/// A product whose notice is generated from the shared configuration
+#[derive(Serialize, Deserialize, JsonSchema, Debug, PartialEq, Eq)]
+pub(crate) struct Target {
+    /// name of the product, printed in the opening sentence of its notice
+    pub(crate) subject: String,
+}
+
 /// Represent a configuration file for a particular project
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
 pub(crate) struct Config {
     /// packages that are build-only dependencies, are not linked/distributed, and are ignored in the build log
     pub(crate) build_only: BTreeSet<String>,
@@ -102,57 +288,471 @@ pub(crate) struct Config {
     pub(crate) vendor: BTreeMap<String, VendorPackage>,
     /// 3rd party packages that are allowed to be build dependencies
     pub(crate) third_party: BTreeMap<String, Package>,
+    /// products sharing the configuration, keyed by the directory containing the BOM of each
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub(crate) targets: BTreeMap<String, Target>,
+    /// text printed in place of a missing copyright statement, overriding the default wording
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) copyright_placeholder: Option<String>,
+    /// SPDX id chosen for every package whose licenses are combined with OR and include it, unless the package records its own choice
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) default_or_choice: Option<String>,
+    /// SPDX ids of the licenses under which no distributed crate may be used, whatever the other checks
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub(crate) denied_licenses: BTreeSet<String>,
+}
+
+impl Config {
+    /// Read a configuration file (or http(s) URL) and check it for consistency,
+    /// leaving the OR packages without a recorded choice as they are
+    pub(crate) fn read(path: &Path) -> Result<Self, anyhow::Error> {
+        let url = path
+            .to_str()
+            .filter(|x| x.starts_with("http://") || x.starts_with("https://"));
+        let config: Config = match url {
+            Some(url) => Self::fetch(url)?,
+            None => serde_json::from_reader(std::fs::File::open(path)?)?,
+        };
+        config.validate()?;
+        crate::warnings::info(format!(
+            "loaded {} with {} third_party package(s)",
+            path.display(),
+            config.third_party.len()
+        ));
+        Ok(config)
+    }
+
+    /// Load several configurations and merge them in order, later ones overriding earlier ones
+    ///
+    /// Unless `force` is set, a third_party entry may only be overridden by one with the same licenses.
+    /// The `default_or_choice` of the merged configuration applies to the packages of every file.
+    pub(crate) fn load_all(paths: &[PathBuf], force: bool) -> Result<Self, anyhow::Error> {
+        let mut config: Option<Config> = None;
+        for path in paths {
+            let other = Self::read(path)?;
+            config = Some(match config {
+                None => other,
+                Some(config) => config.merge(other, force).map_err(|err| {
+                    anyhow::Error::msg(format!("Unable to merge {}: {err}", path.display()))
+                })?,
+            });
+        }
+        let mut config =
+            config.ok_or_else(|| anyhow::Error::msg("No configuration was provided"))?;
+        config.apply_default_or_choice();
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Merge `other` into this configuration, the entries of `other` replacing those with the same key
+    ///
+    /// A crate listed in a different bucket of `other` is moved to that bucket.
+    /// Unless `force` is set, replacing a third_party entry with one under other licenses is an error.
+    pub(crate) fn merge(mut self, other: Config, force: bool) -> Result<Self, anyhow::Error> {
+        for (name, pkg) in other.third_party {
+            if let Some(existing) = self.third_party.get(&name) {
+                let (before, after) = (existing.offered_expression(), pkg.offered_expression());
+                if !force && before.normalized() != after.normalized() {
+                    return Err(anyhow::Error::msg(format!(
+                        "{name} is licensed under {before} but overridden with {after}, use --force to allow it"
+                    )));
+                }
+            }
+            self.build_only.remove(&name);
+            self.vendor.remove(&name);
+            self.third_party.insert(name, pkg);
+        }
+        for (name, vendor) in other.vendor {
+            self.build_only.remove(&name);
+            self.third_party.remove(&name);
+            self.vendor.insert(name, vendor);
+        }
+        for name in other.build_only {
+            self.vendor.remove(&name);
+            self.third_party.remove(&name);
+            self.build_only.insert(name);
+        }
+        self.targets.extend(other.targets);
+        if other.copyright_placeholder.is_some() {
+            self.copyright_placeholder = other.copyright_placeholder;
+        }
+        if other.default_or_choice.is_some() {
+            self.default_or_choice = other.default_or_choice;
+        }
+        self.denied_licenses.extend(other.denied_licenses);
+        Ok(self)
+    }
+
+    #[cfg(feature = "network")]
+    fn fetch(url: &str) -> Result<Self, anyhow::Error> {
+        serde_json::from_reader(crate::urls::fetch(url)?)
+            .map_err(|err| anyhow::Error::msg(format!("Invalid configuration at {url}: {err}")))
+    }
+
+    #[cfg(not(feature = "network"))]
+    fn fetch(url: &str) -> Result<Self, anyhow::Error> {
+        Err(anyhow::Error::msg(format!(
+            "Unable to read {url}: reading the configuration from a URL requires the 'network' feature"
+        )))
+    }
+
+    /// Choose the default license for the OR packages that do not record their own choice
+    pub(crate) fn apply_default_or_choice(&mut self) {
+        let default = match &self.default_or_choice {
+            Some(x) => x,
+            None => return,
+        };
+        for pkg in self.third_party.values_mut() {
+            let applies = pkg.chosen.is_none()
+                && pkg.combine == Combine::Or
+                && pkg
+                    .licenses
+                    .iter()
+                    .any(|x| !matches!(x, License::Unknown) && x.spdx_short() == default);
+            if applies {
+                pkg.chosen = Some(default.clone());
+            }
+        }
+    }
+
+    /// Text printed in place of a missing copyright statement
+    pub(crate) fn copyright_placeholder(&self) -> &str {
+        self.copyright_placeholder
+            .as_deref()
+            .unwrap_or(DEFAULT_COPYRIGHT_PLACEHOLDER)
+    }
+
+    /// True if the crate is listed in any of the buckets
+    pub(crate) fn contains(&self, name: &str) -> bool {
+        self.build_only.contains(name)
+            || self.vendor.contains_key(name)
+            || self.third_party.contains_key(name)
+    }
+
+    /// Find the name under which a crate is listed in any of the buckets, ignoring ASCII case
+    pub(crate) fn find_ignore_case(&self, name: &str) -> Option<&str> {
+        self.build_only
+            .iter()
+            .chain(self.vendor.keys())
+            .chain(self.third_party.keys())
+            .find(|x| x.eq_ignore_ascii_case(name))
+            .map(|x| x.as_str())
+    }
+
+    /// Check the configuration for mistakes that would produce a misleading report
+    pub(crate) fn validate(&self) -> Result<(), anyhow::Error> {
+        // a crate listed in several buckets is only handled by the first one, hiding its obligations
+        let mut buckets: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        for (bucket, keys) in [
+            ("build_only", self.build_only.iter().collect::<Vec<_>>()),
+            ("vendor", self.vendor.keys().collect()),
+            ("third_party", self.third_party.keys().collect()),
+        ] {
+            for key in keys {
+                buckets.entry(key).or_default().push(bucket);
+            }
+        }
+        let overlaps: Vec<String> = buckets
+            .iter()
+            .filter(|(_, buckets)| buckets.len() > 1)
+            .map(|(key, buckets)| format!("{key} ({})", buckets.join(", ")))
+            .collect();
+        if !overlaps.is_empty() {
+            return Err(anyhow::Error::msg(format!(
+                "Crates must be listed in only one of build_only, vendor and third_party: {}",
+                overlaps.join(", ")
+            )));
+        }
+
+        // a custom license is printed once, so every package must describe it the same way
+        let mut custom: BTreeMap<&str, (&String, &String, &String)> = BTreeMap::new();
+        // likewise for the clause added to BSD-3-Clause
+        let mut extra_clause: Option<(&String, &String)> = None;
+        for (name, pkg) in self.third_party.iter() {
+            if pkg.id != *name {
+                return Err(anyhow::Error::msg(format!(
+                    "The id {:?} of the third_party entry {name} must be the same as its key",
+                    pkg.id
+                )));
+            }
+            let mut seen = BTreeSet::new();
+            for license in pkg.licenses.iter() {
+                if let License::Unknown = license {
+                    continue;
+                }
+                let spdx = license.spdx_short();
+                if let License::Custom { url, text, .. } = license {
+                    // the id names the file the text is written to with --license-dir
+                    let valid = !spdx.is_empty()
+                        && spdx
+                            .chars()
+                            .all(|x| x.is_ascii_alphanumeric() || matches!(x, '.' | '+' | '-'));
+                    if !valid {
+                        return Err(anyhow::Error::msg(format!(
+                            "The custom license id {spdx:?} of {name} may only contain letters, digits, '.', '+' and '-'"
+                        )));
+                    }
+                    if License::from_spdx(spdx, Copyright::NotPresent).is_some() {
+                        return Err(anyhow::Error::msg(format!(
+                            "The custom license {spdx} of {name} is bundled and must not be described in the configuration"
+                        )));
+                    }
+                    let previous = custom.insert(spdx, (url, text, name));
+                    if let Some((_, _, other)) = previous.filter(|x| x.0 != url || x.1 != text) {
+                        return Err(anyhow::Error::msg(format!(
+                            "The custom license {spdx} is described differently for {other} and {name}"
+                        )));
+                    }
+                }
+                if let License::Bsd3 {
+                    extra_clause: Some(clause),
+                    ..
+                } = license
+                {
+                    match extra_clause {
+                        Some((previous, other)) if previous != clause => {
+                            return Err(anyhow::Error::msg(format!(
+                                "BSD-3-Clause has different extra clauses for {other} and {name}"
+                            )));
+                        }
+                        _ => extra_clause = Some((clause, name)),
+                    }
+                }
+                if !seen.insert(spdx) {
+                    return Err(anyhow::Error::msg(format!(
+                        "License {spdx} is listed more than once for {name}"
+                    )));
+                }
+            }
+            if let Some(expression) = &pkg.expression {
+                if pkg.combine != Combine::And {
+                    return Err(anyhow::Error::msg(format!(
+                        "{name} has a license expression, so its licenses must not be combined with OR"
+                    )));
+                }
+                let parsed = Expression::parse(expression).map_err(|err| {
+                    anyhow::Error::msg(format!("Invalid license expression of {name}: {err}"))
+                })?;
+                let ids = parsed.ids();
+                if let Some(id) = ids.iter().find(|x| !seen.contains(*x)) {
+                    return Err(anyhow::Error::msg(format!(
+                        "The license expression of {name} refers to {id}, which is not one of its licenses"
+                    )));
+                }
+                if let Some(spdx) = seen.iter().find(|x| !ids.contains(x)) {
+                    return Err(anyhow::Error::msg(format!(
+                        "License {spdx} of {name} is not used in its license expression"
+                    )));
+                }
+            }
+            match &pkg.chosen {
+                Some(_) if pkg.combine != Combine::Or => {
+                    return Err(anyhow::Error::msg(format!(
+                        "A license is chosen for {name} but its licenses are not combined with OR"
+                    )));
+                }
+                Some(chosen) if !seen.contains(chosen.as_str()) => {
+                    return Err(anyhow::Error::msg(format!(
+                        "The chosen license {chosen} is not one of the licenses of {name}"
+                    )));
+                }
+                None if pkg.chosen_rationale.is_some() => {
+                    return Err(anyhow::Error::msg(format!(
+                        "A rationale is given for {name} but no license is chosen"
+                    )));
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
 }
 
 impl License {
+    /// Look up a license by its SPDX id, using `copyright` for licenses that carry a copyright statement
+    pub(crate) fn from_spdx(spdx: &str, copyright: Copyright) -> Option<License> {
+        let license = match spdx {
+            "ISC" => License::Isc { copyright },
+            "MIT" => License::Mit { copyright },
+            "OpenSSL" => License::OpenSsl,
+            "BSL-1.0" => License::Bsl1,
+            "MPL-1.1" => License::Mpl1,
+            "MPL-2.0" => License::Mpl2,
+            "BSD-3-Clause" => License::Bsd3 {
+                copyright,
+                extra_clause: None,
+            },
+            "BSD-2-Clause" => License::Bsd2 { copyright },
+            "Zlib" => License::Zlib { copyright },
+            "0BSD" => License::ZeroBsd,
+            "Unicode-DFS-2016" => License::UnicodeDfs2016,
+            "WTFPL" => License::Wtfpl,
+            "EUPL-1.2" => License::Eupl12,
+            "Apache-2.0" => License::Apache2 { notice: None },
+            "GPL-3.0-only" => License::Gpl3 {
+                or_later: false,
+                exception: None,
+            },
+            "GPL-3.0-or-later" => License::Gpl3 {
+                or_later: true,
+                exception: None,
+            },
+            "GPL-3.0-only WITH GCC-exception-3.1" => License::Gpl3 {
+                or_later: false,
+                exception: Some(Gpl3Exception::Gcc31),
+            },
+            "GPL-3.0-or-later WITH GCC-exception-3.1" => License::Gpl3 {
+                or_later: true,
+                exception: Some(Gpl3Exception::Gcc31),
+            },
+            _ => return None,
+        };
+        Some(license)
+    }
+
     /// Information about the license
-    pub(crate) fn info(&self) -> LicenseInfo {
+    pub(crate) fn info(&self) -> LicenseInfo<'_> {
         LicenseInfo {
             url: self.url(),
             text: self.text(),
+            note: self.note(),
         }
     }
 
-    /// Optional copyright lines provided by the author(s)
-    pub(crate) fn copyright(&self) -> Option<Vec<String>> {
+    /// Optional copyright lines provided by the author(s), or the `placeholder` if they provided none
+    pub(crate) fn copyright(&self, placeholder: &str) -> Option<Vec<String>> {
+        self.copyright_statement().map(|x| x.lines(placeholder))
+    }
+
+    /// True if the author(s) provided an actual copyright statement
+    pub(crate) fn has_copyright(&self) -> bool {
+        matches!(self.copyright_statement(), Some(Copyright::Lines(_)))
+    }
+
+    /// True if the license requires a copyright statement but the author(s) did not provide one
+    pub(crate) fn is_copyright_missing(&self) -> bool {
+        matches!(self.copyright_statement(), Some(Copyright::NotPresent))
+    }
+
+    fn copyright_statement(&self) -> Option<&Copyright> {
         match self {
             License::Unknown => None,
-            License::Isc { copyright } => Some(copyright.lines()),
-            License::Mit { copyright } => Some(copyright.lines()),
+            License::Isc { copyright } => Some(copyright),
+            License::Mit { copyright } => Some(copyright),
             License::OpenSsl => None,
             License::Bsl1 => None,
+            License::Mpl1 => None,
             License::Mpl2 => None,
-            License::Bsd3 { copyright } => Some(copyright.lines()),
+            License::Bsd3 { copyright, .. } => Some(copyright),
+            License::Bsd2 { copyright } => Some(copyright),
+            License::Zlib { copyright } => Some(copyright),
+            License::ZeroBsd => None,
             License::UnicodeDfs2016 => None,
+            License::Wtfpl => None,
+            License::Eupl12 => None,
+            License::Apache2 { .. } => None,
+            License::Custom { copyright, .. } => Some(copyright),
+            License::Gpl3 { .. } => None,
         }
     }
 
-    /// The text of the license itself
-    pub(crate) fn text(&self) -> &'static str {
+    /// Lines of the NOTICE file that must be distributed along with the license, if any
+    pub(crate) fn notice(&self) -> Option<&[String]> {
         match self {
+            License::Apache2 {
+                notice: Some(notice),
+            } => Some(notice),
+            _ => None,
+        }
+    }
+
+    /// Remark about the license that recipients of the report should be aware of
+    fn note(&self) -> Option<&'static str> {
+        match self {
+            License::Eupl12 => {
+                Some("the appendix of the EUPL lists the licenses it is compatible with")
+            }
+            _ => None,
+        }
+    }
+
+    /// The text of the license itself
+    pub(crate) fn text(&self) -> Cow<'_, str> {
+        if let License::Bsd3 {
+            extra_clause: Some(clause),
+            ..
+        } = self
+        {
+            return Cow::Owned(format!(
+                "{}\n\n{clause}\n",
+                std::include_str!("../licenses/bsd3.txt").trim_end()
+            ));
+        }
+        Cow::Borrowed(match self {
             License::Isc { .. } => std::include_str!("../licenses/isc.txt"),
             License::Mit { .. } => std::include_str!("../licenses/mit.txt"),
             License::OpenSsl => std::include_str!("../licenses/openssl.txt"),
             License::Bsl1 => std::include_str!("../licenses/bsl.txt"),
+            License::Mpl1 => std::include_str!("../licenses/mpl1.txt"),
             License::Mpl2 => std::include_str!("../licenses/mpl2.txt"),
             License::Bsd3 { .. } => std::include_str!("../licenses/bsd3.txt"),
+            License::Bsd2 { .. } => std::include_str!("../licenses/bsd2.txt"),
+            License::Zlib { .. } => std::include_str!("../licenses/zlib.txt"),
+            License::ZeroBsd => std::include_str!("../licenses/0bsd.txt"),
             License::UnicodeDfs2016 => {
                 std::include_str!("../licenses/unicode_dfs_2016.txt")
             }
+            License::Wtfpl => std::include_str!("../licenses/wtfpl.txt"),
+            License::Eupl12 => std::include_str!("../licenses/eupl12.txt"),
+            License::Apache2 { .. } => std::include_str!("../licenses/apache2.txt"),
+            License::Custom { text, .. } => text,
+            License::Gpl3 {
+                exception: None, ..
+            } => std::include_str!("../licenses/gpl3.txt"),
+            License::Gpl3 {
+                exception: Some(Gpl3Exception::Gcc31),
+                ..
+            } => std::concat!(
+                std::include_str!("../licenses/gpl3.txt"),
+                "\n",
+                std::include_str!("../licenses/gcc_exception_3_1.txt")
+            ),
             License::Unknown => panic!("You must define unknown licenses"),
-        }
+        })
     }
 
     /// SPDX short abbreviation for the license
-    pub(crate) fn spdx_short(&self) -> &'static str {
+    pub(crate) fn spdx_short(&self) -> &str {
         match self {
             License::Isc { .. } => "ISC",
             License::Mit { .. } => "MIT",
             License::OpenSsl => "OpenSSL",
             License::Bsl1 => "BSL-1.0",
+            License::Mpl1 => "MPL-1.1",
             License::Mpl2 => "MPL-2.0",
+            // the modified license is not on the SPDX list, so it must not be mistaken for the original
+            License::Bsd3 {
+                extra_clause: Some(_),
+                ..
+            } => "LicenseRef-BSD-3-Clause-Extra",
             License::Bsd3 { .. } => "BSD-3-Clause",
+            License::Bsd2 { .. } => "BSD-2-Clause",
+            License::Zlib { .. } => "Zlib",
+            License::ZeroBsd => "0BSD",
             License::UnicodeDfs2016 => "Unicode-DFS-2016",
+            License::Wtfpl => "WTFPL",
+            License::Eupl12 => "EUPL-1.2",
+            License::Apache2 { .. } => "Apache-2.0",
+            License::Custom { spdx, .. } => spdx,
+            License::Gpl3 {
+                or_later,
+                exception,
+            } => match (or_later, exception) {
+                (false, None) => "GPL-3.0-only",
+                (true, None) => "GPL-3.0-or-later",
+                (false, Some(Gpl3Exception::Gcc31)) => "GPL-3.0-only WITH GCC-exception-3.1",
+                (true, Some(Gpl3Exception::Gcc31)) => "GPL-3.0-or-later WITH GCC-exception-3.1",
+            },
             License::Unknown => {
                 panic!("You must define unknown licenses")
             }
@@ -160,18 +760,369 @@ impl License {
     }
 
     /// The URL with information about the license
-    pub(crate) fn url(&self) -> &'static str {
+    pub(crate) fn url(&self) -> &str {
         match self {
             License::Isc { .. } => "https://spdx.org/licenses/ISC.html",
             License::Mit { .. } => "https://spdx.org/licenses/MIT.html",
             License::OpenSsl => "https://spdx.org/licenses/OpenSSL.html",
             License::Bsl1 => "https://spdx.org/licenses/BSL-1.0.html",
+            License::Mpl1 => "https://spdx.org/licenses/MPL-1.1.html",
             License::Mpl2 => "https://spdx.org/licenses/MPL-2.0.html",
             License::Bsd3 { .. } => "https://spdx.org/licenses/BSD-3-Clause.html",
+            License::Bsd2 { .. } => "https://spdx.org/licenses/BSD-2-Clause.html",
+            License::Zlib { .. } => "https://spdx.org/licenses/Zlib.html",
+            License::ZeroBsd => "https://spdx.org/licenses/0BSD.html",
             License::UnicodeDfs2016 => "https://spdx.org/licenses/Unicode-DFS-2016.html",
+            License::Wtfpl => "https://spdx.org/licenses/WTFPL.html",
+            License::Eupl12 => "https://spdx.org/licenses/EUPL-1.2.html",
+            License::Apache2 { .. } => "https://spdx.org/licenses/Apache-2.0.html",
+            License::Custom { url, .. } => url,
+            License::Gpl3 {
+                or_later: false, ..
+            } => "https://spdx.org/licenses/GPL-3.0-only.html",
+            License::Gpl3 { or_later: true, .. } => {
+                "https://spdx.org/licenses/GPL-3.0-or-later.html"
+            }
             License::Unknown => {
                 panic!("You must define unknown licenses")
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    const PACKAGES: &str = r#"{"build_only":[],"vendor":{},"third_party":{
+        "either":{"id":"either","source":"crates.io","combine":"OR","licenses":[{"ISC":{"copyright":"NotPresent"}},{"MIT":{"copyright":"NotPresent"}}]}}}"#;
+    const DEFAULT_MIT: &str =
+        r#"{"build_only":["cc"],"vendor":{},"third_party":{},"default_or_choice":"MIT"}"#;
+
+    #[test]
+    fn git_source_round_trips_from_a_vcs_url() {
+        let source = Source::from_vcs_url("git+https://github.com/stepfunc/dnp3#1.5.0");
+        assert_eq!(
+            source,
+            Source::Git {
+                repo: "https://github.com/stepfunc/dnp3".to_string(),
+                rev: Some("1.5.0".to_string()),
+            }
+        );
+        let json = serde_json::to_string(&source).unwrap();
+        assert_eq!(
+            json,
+            r#"{"git":{"repo":"https://github.com/stepfunc/dnp3","rev":"1.5.0"}}"#
+        );
+        assert_eq!(serde_json::from_str::<Source>(&json).unwrap(), source);
+
+        let source = Source::from_vcs_url("https://github.com/stepfunc/dnp3");
+        let json = serde_json::to_string(&source).unwrap();
+        assert_eq!(
+            json,
+            r#"{"git":{"repo":"https://github.com/stepfunc/dnp3"}}"#
+        );
+    }
+
+    fn parse(json: &str) -> Config {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn merge_unions_the_buckets_and_moves_crates_between_them() {
+        let base = parse(
+            r#"{"build_only":["cc","libc"],"vendor":{},"denied_licenses":["GPL-3.0-only"],"third_party":{
+                "serde":{"id":"serde","source":"crates.io","licenses":[{"MIT":{"copyright":"NotPresent"}}]}}}"#,
+        );
+        let other = parse(
+            r#"{"build_only":[],"vendor":{},"denied_licenses":["AGPL-3.0-only"],"third_party":{
+                "libc":{"id":"libc","source":"crates.io","licenses":[{"MIT":{"copyright":"NotPresent"}}]},
+                "serde":{"id":"serde","source":"crates.io","comment":"reviewed","licenses":[{"MIT":{"copyright":{"Lines":["Copyright (c) Serde"]}}}]}}}"#,
+        );
+
+        let merged = base.merge(other, false).unwrap();
+        assert_eq!(merged.build_only, BTreeSet::from(["cc".to_string()]));
+        assert_eq!(
+            merged.third_party.keys().collect::<Vec<_>>(),
+            ["libc", "serde"]
+        );
+        assert_eq!(
+            merged.third_party["serde"].comment.as_deref(),
+            Some("reviewed")
+        );
+        assert_eq!(
+            merged.denied_licenses,
+            BTreeSet::from(["AGPL-3.0-only".to_string(), "GPL-3.0-only".to_string()])
+        );
+    }
+
+    #[test]
+    fn merge_rejects_overriding_the_licenses_unless_forced() {
+        let mit = r#"{"build_only":[],"vendor":{},"third_party":{
+            "either":{"id":"either","source":"crates.io","licenses":[{"MIT":{"copyright":"NotPresent"}}]}}}"#;
+
+        let err = parse(PACKAGES).merge(parse(mit), false).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "either is licensed under ISC OR MIT but overridden with MIT, use --force to allow it"
+        );
+
+        let merged = parse(PACKAGES).merge(parse(mit), true).unwrap();
+        assert_eq!(merged.third_party["either"].combine, Combine::And);
+    }
+
+    #[test]
+    fn combine_is_only_written_for_or() {
+        let config: Config = serde_json::from_str(PACKAGES).unwrap();
+        assert_eq!(config.third_party["either"].combine, Combine::Or);
+        let json = serde_json::to_value(&config).unwrap();
+        assert_eq!(json["third_party"]["either"]["combine"], "OR");
+
+        let json = PACKAGES.replace(r#""combine":"OR","#, "");
+        let config: Config = serde_json::from_str(&json).unwrap();
+        assert_eq!(config.third_party["either"].combine, Combine::And);
+        let json = serde_json::to_value(&config).unwrap();
+        assert!(json["third_party"]["either"].get("combine").is_none());
+    }
+
+    #[cfg(feature = "network")]
+    #[test]
+    fn reads_the_configuration_from_a_url() {
+        let url = crate::test_util::serve("200 OK", PACKAGES.to_string());
+
+        let config = Config::read(Path::new(&url)).unwrap();
+        assert_eq!(config.third_party["either"].combine, Combine::Or);
+    }
+
+    #[cfg(feature = "network")]
+    #[test]
+    fn invalid_configuration_at_a_url_is_an_error() {
+        let url = crate::test_util::serve("200 OK", "{}".to_string());
+
+        let err = Config::read(Path::new(&url)).unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with(&format!("Invalid configuration at {url}: ")));
+    }
+
+    #[cfg(not(feature = "network"))]
+    #[test]
+    fn reading_a_url_requires_the_network_feature() {
+        let err = Config::read(Path::new("https://example.com/allow-list.json")).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Unable to read https://example.com/allow-list.json: reading the configuration from a URL requires the 'network' feature"
+        );
+    }
+
+    #[test]
+    fn wtfpl_round_trips_without_a_copyright() {
+        let license: License = serde_json::from_str(r#""WTFPL""#).unwrap();
+        assert_eq!(license, License::Wtfpl);
+        assert_eq!(serde_json::to_string(&license).unwrap(), r#""WTFPL""#);
+
+        assert_eq!(license.spdx_short(), "WTFPL");
+        assert_eq!(license.url(), "https://spdx.org/licenses/WTFPL.html");
+        assert!(!license.has_copyright());
+        assert!(license
+            .text()
+            .contains("DO WHAT THE FUCK YOU WANT TO PUBLIC LICENSE"));
+        assert_eq!(
+            License::from_spdx("WTFPL", Copyright::NotPresent),
+            Some(License::Wtfpl)
+        );
+    }
+
+    #[test]
+    fn eupl_round_trips_as_copyleft_with_a_note() {
+        let license: License = serde_json::from_str(r#""EUPLv1.2""#).unwrap();
+        assert_eq!(license, License::Eupl12);
+        assert_eq!(serde_json::to_string(&license).unwrap(), r#""EUPLv1.2""#);
+
+        assert_eq!(license.spdx_short(), "EUPL-1.2");
+        assert_eq!(license.url(), "https://spdx.org/licenses/EUPL-1.2.html");
+        assert!(license.note().is_some());
+        assert_eq!(
+            crate::compat::category(license.spdx_short()),
+            Some(crate::compat::Category::Copyleft)
+        );
+        assert_eq!(
+            License::from_spdx("EUPL-1.2", Copyright::NotPresent),
+            Some(License::Eupl12)
+        );
+    }
+
+    #[test]
+    fn custom_license_round_trips() {
+        let json = r#"{"Custom":{"spdx":"LicenseRef-Foo","url":"https://example.com/foo","text":"Foo license text","copyright":{"Lines":["Copyright Foo"]}}}"#;
+        let license: License = serde_json::from_str(json).unwrap();
+        assert_eq!(serde_json::to_string(&license).unwrap(), json);
+
+        assert_eq!(license.spdx_short(), "LicenseRef-Foo");
+        assert_eq!(license.url(), "https://example.com/foo");
+        assert_eq!(license.text(), "Foo license text");
+        assert!(license.has_copyright());
+    }
+
+    fn validate(json: &str) -> Result<(), anyhow::Error> {
+        serde_json::from_str::<Config>(json).unwrap().validate()
+    }
+
+    #[test]
+    fn a_license_may_only_be_listed_once_per_crate() {
+        let err = validate(
+            r#"{"build_only":[],"vendor":{},"third_party":{"foo":{"id":"foo","source":"crates.io","licenses":[
+                {"MIT":{"copyright":"NotPresent"}},{"MIT":{"copyright":{"Lines":["Copyright (c) Foo"]}}}]}}}"#,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "License MIT is listed more than once for foo"
+        );
+
+        // the same license for different crates is fine
+        validate(
+            r#"{"build_only":[],"vendor":{},"third_party":{
+                "foo":{"id":"foo","source":"crates.io","licenses":[{"MIT":{"copyright":"NotPresent"}}]},
+                "bar":{"id":"bar","source":"crates.io","licenses":[{"MIT":{"copyright":"NotPresent"}}]}}}"#,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn mpl1_round_trips_separately_from_mpl2() {
+        let licenses: Vec<License> = serde_json::from_str(r#"["MPLv1.1", "MPLv2"]"#).unwrap();
+        assert_eq!(licenses, [License::Mpl1, License::Mpl2]);
+        assert_eq!(
+            serde_json::to_string(&licenses).unwrap(),
+            r#"["MPLv1.1","MPLv2"]"#
+        );
+
+        assert_eq!(licenses[0].spdx_short(), "MPL-1.1");
+        assert_eq!(licenses[1].spdx_short(), "MPL-2.0");
+        assert_ne!(licenses[0].url(), licenses[1].url());
+        assert_ne!(licenses[0].text(), licenses[1].text());
+        assert_eq!(
+            crate::compat::category("MPL-1.1"),
+            Some(crate::compat::Category::WeakCopyleft)
+        );
+    }
+
+    #[test]
+    fn id_must_match_the_key() {
+        let err = validate(
+            r#"{"build_only":[],"vendor":{},"third_party":{
+                "openssl":{"id":"OpenSSL","source":"crates.io","licenses":["OpenSSL"]}}}"#,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "The id \"OpenSSL\" of the third_party entry openssl must be the same as its key"
+        );
+    }
+
+    #[test]
+    fn bsd2_zlib_and_0bsd_round_trip() {
+        let cases = [
+            (
+                r#"{"BSD2":{"copyright":"NotPresent"}}"#,
+                "BSD-2-Clause",
+                true,
+            ),
+            (
+                r#"{"Zlib":{"copyright":{"Lines":["Copyright (C) 1995 Jean-loup Gailly"]}}}"#,
+                "Zlib",
+                true,
+            ),
+            (r#""0BSD""#, "0BSD", false),
+        ];
+        for (json, spdx, requires_copyright) in cases {
+            let license: License = serde_json::from_str(json).unwrap();
+            assert_eq!(serde_json::to_string(&license).unwrap(), json);
+            assert_eq!(license.spdx_short(), spdx);
+            assert_eq!(
+                license.has_copyright() || license.is_copyright_missing(),
+                requires_copyright
+            );
+            assert!(license.text().contains(&format!(" {spdx} ")));
+            assert_eq!(
+                license.url(),
+                format!("https://spdx.org/licenses/{spdx}.html")
+            );
+        }
+    }
+
+    #[test]
+    fn crates_must_be_listed_in_only_one_bucket() {
+        let err = validate(
+            r#"{"build_only":["cc","libc","serde"],"vendor":{"libc":{"url":"https://example.com"}},"third_party":{
+                "serde":{"id":"serde","source":"crates.io","licenses":[{"MIT":{"copyright":"NotPresent"}}]}}}"#,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Crates must be listed in only one of build_only, vendor and third_party: \
+             libc (build_only, vendor), serde (build_only, third_party)"
+        );
+    }
+
+    #[test]
+    fn default_or_choice_applies_to_every_merged_file() {
+        let dir = TempDir::new("default-or-choice");
+        let packages = dir.write("packages.json", PACKAGES);
+        let base = dir.write("base.json", DEFAULT_MIT);
+
+        for paths in [[&packages, &base], [&base, &packages]] {
+            let paths: Vec<PathBuf> = paths.iter().map(|x| x.to_path_buf()).collect();
+            let config = Config::load_all(&paths, false).unwrap();
+            assert_eq!(config.third_party["either"].chosen.as_deref(), Some("MIT"));
+            assert!(config.build_only.contains("cc"));
+        }
+    }
+
+    fn custom(spdx: &str) -> Result<(), anyhow::Error> {
+        let json = format!(
+            r#"{{"build_only":[],"vendor":{{}},"third_party":{{"foo":{{"id":"foo","source":"crates.io","licenses":[
+                {{"Custom":{{"spdx":{spdx:?},"url":"https://example.com","text":"text","copyright":"NotPresent"}}}}]}}}}}}"#
+        );
+        serde_json::from_str::<Config>(&json).unwrap().validate()
+    }
+
+    #[test]
+    fn custom_license_ids_must_be_valid_file_names() {
+        assert!(custom("LicenseRef-Foo-1.0").is_ok());
+        assert!(custom("Foo-2.0+").is_ok());
+        for spdx in ["../../etc/passwd", "a/b", "a\\b", "", "Foo 1.0"] {
+            let err = custom(spdx).unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                format!("The custom license id {spdx:?} of foo may only contain letters, digits, '.', '+' and '-'")
+            );
+        }
+    }
+
+    #[test]
+    fn read_leaves_or_packages_without_a_choice() {
+        let dir = TempDir::new("read-raw");
+        let path = dir.write(
+            "config.json",
+            &PACKAGES.replacen(
+                "{\"build_only\"",
+                "{\"default_or_choice\":\"MIT\",\"build_only\"",
+                1,
+            ),
+        );
+
+        assert_eq!(
+            Config::read(&path).unwrap().third_party["either"].chosen,
+            None
+        );
+        assert_eq!(
+            Config::load_all(&[path], false).unwrap().third_party["either"]
+                .chosen
+                .as_deref(),
+            Some("MIT")
+        );
+    }
+}
@@ -0,0 +1,134 @@
+use crate::config::Config;
+
+/// Print a JSON Schema of the configuration file for use by editors
+pub(crate) fn config_schema<W>(w: W) -> Result<(), anyhow::Error>
+where
+    W: std::io::Write,
+{
+    let schema = schemars::schema_for!(Config);
+    serde_json::to_writer_pretty(w, &schema)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    /// Check a value against the subset of JSON Schema that schemars emits for the configuration
+    fn validates(schema: &Value, root: &Value, value: &Value) -> bool {
+        if let Some(path) = schema.get("$ref").and_then(Value::as_str) {
+            let name = path.trim_start_matches("#/definitions/");
+            return validates(&root["definitions"][name], root, value);
+        }
+        if let Some(types) = schema.get("type") {
+            let types: Vec<&str> = match types {
+                Value::Array(x) => x.iter().filter_map(Value::as_str).collect(),
+                x => x.as_str().into_iter().collect(),
+            };
+            let matches = |t: &str| match t {
+                "null" => value.is_null(),
+                "boolean" => value.is_boolean(),
+                "string" => value.is_string(),
+                "integer" => value.is_u64() || value.is_i64(),
+                "array" => value.is_array(),
+                "object" => value.is_object(),
+                _ => false,
+            };
+            if !types.into_iter().any(matches) {
+                return false;
+            }
+        }
+        if let Some(values) = schema.get("enum").and_then(Value::as_array) {
+            if !values.contains(value) {
+                return false;
+            }
+        }
+        if let Some(all) = schema.get("allOf").and_then(Value::as_array) {
+            if !all.iter().all(|x| validates(x, root, value)) {
+                return false;
+            }
+        }
+        for key in ["oneOf", "anyOf"] {
+            if let Some(any) = schema.get(key).and_then(Value::as_array) {
+                if !any.iter().any(|x| validates(x, root, value)) {
+                    return false;
+                }
+            }
+        }
+        if let Some(items) = schema.get("items") {
+            if let Some(array) = value.as_array() {
+                if !array.iter().all(|x| validates(items, root, x)) {
+                    return false;
+                }
+            }
+        }
+        if let Some(object) = value.as_object() {
+            let required = schema.get("required").and_then(Value::as_array);
+            if !required
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+                .all(|x| object.contains_key(x))
+            {
+                return false;
+            }
+            let properties = schema.get("properties").and_then(Value::as_object);
+            for (key, item) in object {
+                let valid = match (
+                    properties.and_then(|x| x.get(key)),
+                    schema.get("additionalProperties"),
+                ) {
+                    (Some(property), _) => validates(property, root, item),
+                    (None, Some(Value::Bool(allowed))) => *allowed,
+                    (None, Some(additional)) => validates(additional, root, item),
+                    (None, None) => true,
+                };
+                if !valid {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    fn schema() -> Value {
+        let mut out = Vec::new();
+        config_schema(&mut out).unwrap();
+        serde_json::from_slice(&out).unwrap()
+    }
+
+    #[test]
+    fn schema_validates_a_known_good_config() {
+        let schema = schema();
+        let config: Value = serde_json::from_str(crate::test_util::CONFIG).unwrap();
+        assert!(validates(&schema, &schema, &config));
+
+        let config = serde_json::json!({
+            "build_only": ["cc"],
+            "vendor": {"app": {"url": "https://example.com"}},
+            "third_party": {
+                "either": {
+                    "id": "either",
+                    "source": "crates.io",
+                    "combine": "OR",
+                    "chosen": "MIT",
+                    "licenses": [{"ISC": {"copyright": "NotPresent"}}, {"MIT": {"copyright": "NotPresent"}}, "MPLv2"]
+                }
+            }
+        });
+        assert!(serde_json::from_value::<Config>(config.clone()).is_ok());
+        assert!(validates(&schema, &schema, &config));
+    }
+
+    #[test]
+    fn schema_rejects_unknown_licenses_and_missing_fields() {
+        let schema = schema();
+        let config = crate::test_util::CONFIG.replace(r#""OpenSSL""#, r#""GPL""#);
+        let config: Value = serde_json::from_str(&config).unwrap();
+        assert!(!validates(&schema, &schema, &config));
+
+        let config = serde_json::json!({"build_only": [], "vendor": {}});
+        assert!(!validates(&schema, &schema, &config));
+    }
+}
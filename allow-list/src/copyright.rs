@@ -0,0 +1,188 @@
+use std::collections::BTreeSet;
+
+/// Canonicalize the copyright symbol and collapse lists of years into ranges
+///
+/// e.g. `COPYRIGHT © 2019, 2020, 2021 Jane Doe` becomes `Copyright (c) 2019-2021 Jane Doe`
+pub(crate) fn normalize(line: &str) -> String {
+    collapse_years(&normalize_symbol(line.trim()))
+}
+
+fn normalize_symbol(line: &str) -> String {
+    let line = line.replace("(C)", "(c)").replace('\u{a9}', "(c)");
+
+    let rest = match line.get(..9) {
+        Some(prefix)
+            if prefix.eq_ignore_ascii_case("copyright")
+                && !line[9..].starts_with(|x: char| x.is_alphanumeric()) =>
+        {
+            &line[9..]
+        }
+        _ if line.starts_with("(c)") => line.as_str(),
+        _ => return line,
+    };
+
+    let mut rest = rest.trim_start();
+    while let Some(x) = rest.strip_prefix("(c)") {
+        rest = x.trim_start();
+    }
+
+    format!("Copyright (c) {rest}")
+}
+
+fn collapse_years(line: &str) -> String {
+    let chars: Vec<char> = line.chars().collect();
+    let mut out = String::new();
+    let mut pos = 0;
+    while pos < chars.len() {
+        let at_boundary = pos == 0 || !chars[pos - 1].is_alphanumeric();
+        match at_boundary.then(|| parse_years(&chars, pos)).flatten() {
+            Some((years, end)) => {
+                out.push_str(&render_years(&years));
+                pos = end;
+            }
+            None => {
+                out.push(chars[pos]);
+                pos += 1;
+            }
+        }
+    }
+    out
+}
+
+/// Parse a four digit year starting at `pos`, returning the year and the position after it
+fn parse_year(chars: &[char], pos: usize) -> Option<(u32, usize)> {
+    let digits = chars.get(pos..pos + 4)?;
+    if !digits.iter().all(|x| x.is_ascii_digit()) {
+        return None;
+    }
+    if chars.get(pos + 4).is_some_and(|x| x.is_alphanumeric()) {
+        return None;
+    }
+    let year: u32 = digits.iter().collect::<String>().parse().ok()?;
+    (1900..2100).contains(&year).then_some((year, pos + 4))
+}
+
+/// Parse a list of years and ranges like `2019, 2020-2022`, returning the years and the end position
+fn parse_years(chars: &[char], pos: usize) -> Option<(BTreeSet<u32>, usize)> {
+    let (mut last, mut end) = parse_year(chars, pos)?;
+    let mut years = BTreeSet::from([last]);
+
+    loop {
+        let mut next = end;
+        let mut range = false;
+        while let Some(c) = chars.get(next) {
+            match c {
+                '-' | '\u{2013}' => range = true,
+                ',' | ' ' => {}
+                _ => break,
+            }
+            next += 1;
+        }
+
+        match parse_year(chars, next) {
+            Some((year, after)) => {
+                if range && last < year {
+                    years.extend(last..=year);
+                } else {
+                    years.insert(year);
+                }
+                last = year;
+                end = after;
+            }
+            None => return Some((years, end)),
+        }
+    }
+}
+
+/// Render years as comma separated ranges of consecutive years
+fn render_years(years: &BTreeSet<u32>) -> String {
+    let mut ranges: Vec<(u32, u32)> = Vec::new();
+    for year in years.iter().copied() {
+        match ranges.last_mut() {
+            Some((_, end)) if *end + 1 == year => *end = year,
+            _ => ranges.push((year, year)),
+        }
+    }
+
+    let ranges: Vec<String> = ranges
+        .into_iter()
+        .map(|(start, end)| {
+            if start == end {
+                start.to_string()
+            } else {
+                format!("{start}-{end}")
+            }
+        })
+        .collect();
+    ranges.join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn canonicalizes_the_copyright_symbol() {
+        assert_eq!(
+            normalize("Copyright (C) 2019 Jane Doe"),
+            "Copyright (c) 2019 Jane Doe"
+        );
+        assert_eq!(
+            normalize("Copyright \u{a9} 2019 Jane Doe"),
+            "Copyright (c) 2019 Jane Doe"
+        );
+        assert_eq!(
+            normalize("\u{a9} 2019 Jane Doe"),
+            "Copyright (c) 2019 Jane Doe"
+        );
+        assert_eq!(
+            normalize("(c) 2019 Jane Doe"),
+            "Copyright (c) 2019 Jane Doe"
+        );
+        assert_eq!(
+            normalize("COPYRIGHT 2019 Jane Doe"),
+            "Copyright (c) 2019 Jane Doe"
+        );
+        assert_eq!(
+            normalize("  Copyright (c) \u{a9} 2019 Jane Doe "),
+            "Copyright (c) 2019 Jane Doe"
+        );
+    }
+
+    #[test]
+    fn leaves_other_words_alone() {
+        assert_eq!(
+            normalize("Copyrighted by Jane Doe"),
+            "Copyrighted by Jane Doe"
+        );
+        assert_eq!(
+            normalize("The Rust Project Developers"),
+            "The Rust Project Developers"
+        );
+    }
+
+    #[test]
+    fn collapses_lists_of_years_into_ranges() {
+        assert_eq!(
+            normalize("Copyright (c) 2019-2023 Jane Doe"),
+            "Copyright (c) 2019-2023 Jane Doe"
+        );
+        assert_eq!(
+            normalize("Copyright (c) 2019, 2020, 2021 Jane Doe"),
+            "Copyright (c) 2019-2021 Jane Doe"
+        );
+        assert_eq!(
+            normalize("Copyright (c) 2015, 2017-2018, 2019 Jane Doe"),
+            "Copyright (c) 2015, 2017-2019 Jane Doe"
+        );
+        assert_eq!(
+            normalize("Copyright (c) 2019\u{2013}2020 Jane Doe"),
+            "Copyright (c) 2019-2020 Jane Doe"
+        );
+        // numbers that are not years are kept
+        assert_eq!(
+            normalize("Copyright (c) 12345 Jane Doe"),
+            "Copyright (c) 12345 Jane Doe"
+        );
+    }
+}
@@ -0,0 +1,308 @@
+use crate::config::{Config, License, Package};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+/// Print the semantic differences between two configuration files
+///
+/// The files are compared as written, i.e. without applying `default_or_choice` to the packages.
+/// Returns an error if `check` is set and any difference was found
+pub(crate) fn diff_config<W>(
+    old_path: &Path,
+    new_path: &Path,
+    check: bool,
+    mut w: W,
+) -> Result<(), anyhow::Error>
+where
+    W: std::io::Write,
+{
+    let old = Config::read(old_path)?;
+    let new = Config::read(new_path)?;
+
+    let mut changes = Vec::new();
+
+    diff_keys("build_only", &old.build_only, &new.build_only, &mut changes);
+    diff_keys(
+        "vendor",
+        &old.vendor.keys().cloned().collect(),
+        &new.vendor.keys().cloned().collect(),
+        &mut changes,
+    );
+    for (name, pkg) in old.vendor.iter() {
+        if let Some(other) = new.vendor.get(name) {
+            if pkg.url != other.url {
+                changes.push(format!(
+                    "vendor: {name} url changed: {} -> {}",
+                    pkg.url, other.url
+                ));
+            }
+        }
+    }
+    diff_keys(
+        "third_party",
+        &old.third_party.keys().cloned().collect(),
+        &new.third_party.keys().cloned().collect(),
+        &mut changes,
+    );
+    diff_packages(&old.third_party, &new.third_party, &mut changes);
+    diff_keys(
+        "targets",
+        &old.targets.keys().cloned().collect(),
+        &new.targets.keys().cloned().collect(),
+        &mut changes,
+    );
+    for (name, target) in old.targets.iter() {
+        if let Some(other) = new.targets.get(name) {
+            if target.subject != other.subject {
+                changes.push(format!(
+                    "targets: {name} subject changed: {} -> {}",
+                    target.subject, other.subject
+                ));
+            }
+        }
+    }
+    diff_option(
+        "copyright_placeholder",
+        &old.copyright_placeholder,
+        &new.copyright_placeholder,
+        &mut changes,
+    );
+    diff_option(
+        "default_or_choice",
+        &old.default_or_choice,
+        &new.default_or_choice,
+        &mut changes,
+    );
+    diff_keys(
+        "denied_licenses",
+        &old.denied_licenses,
+        &new.denied_licenses,
+        &mut changes,
+    );
+    diff_other_fields(&old, &new, &mut changes)?;
+
+    for change in changes.iter() {
+        writeln!(w, "{change}")?;
+    }
+
+    if check && !changes.is_empty() {
+        return Err(anyhow::Error::msg(format!(
+            "{} difference(s) between the configurations",
+            changes.len()
+        )));
+    }
+
+    Ok(())
+}
+
+fn diff_keys(
+    bucket: &str,
+    old: &BTreeSet<String>,
+    new: &BTreeSet<String>,
+    changes: &mut Vec<String>,
+) {
+    for name in new.difference(old) {
+        changes.push(format!("{bucket}: added {name}"));
+    }
+    for name in old.difference(new) {
+        changes.push(format!("{bucket}: removed {name}"));
+    }
+}
+
+/// Report a change of an optional top-level setting
+fn diff_option(field: &str, old: &Option<String>, new: &Option<String>, changes: &mut Vec<String>) {
+    if old != new {
+        let describe = |x: &Option<String>| {
+            x.as_deref()
+                .map_or("none".to_string(), |x| format!("{x:?}"))
+        };
+        changes.push(format!(
+            "{field} changed: {} -> {}",
+            describe(old),
+            describe(new)
+        ));
+    }
+}
+
+/// Report a change of any top-level field that is not compared in detail, so that none goes unnoticed
+fn diff_other_fields(
+    old: &Config,
+    new: &Config,
+    changes: &mut Vec<String>,
+) -> Result<(), anyhow::Error> {
+    const COMPARED: &[&str] = &[
+        "build_only",
+        "vendor",
+        "third_party",
+        "targets",
+        "copyright_placeholder",
+        "default_or_choice",
+        "denied_licenses",
+    ];
+    let (old, new) = (serde_json::to_value(old)?, serde_json::to_value(new)?);
+    let (Some(old), Some(new)) = (old.as_object(), new.as_object()) else {
+        return Ok(());
+    };
+    let fields: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+    for field in fields {
+        if !COMPARED.contains(&field.as_str()) && old.get(field) != new.get(field) {
+            changes.push(format!("{field} changed"));
+        }
+    }
+    Ok(())
+}
+
+fn diff_packages(
+    old: &BTreeMap<String, Package>,
+    new: &BTreeMap<String, Package>,
+    changes: &mut Vec<String>,
+) {
+    for (name, pkg) in old.iter() {
+        let other = match new.get(name) {
+            Some(x) => x,
+            None => continue,
+        };
+
+        if pkg.id != other.id {
+            changes.push(format!(
+                "third_party: {name} id changed: {} -> {}",
+                pkg.id, other.id
+            ));
+        }
+        if pkg.source != other.source {
+            changes.push(format!(
+                "third_party: {name} source changed: {} -> {}",
+                describe_source(pkg),
+                describe_source(other)
+            ));
+        }
+        if pkg.licenses != other.licenses
+            || pkg.combine != other.combine
+            || pkg.expression != other.expression
+            || pkg.chosen != other.chosen
+        {
+            let (before, after) = (describe_licenses(pkg), describe_licenses(other));
+            if before != after {
+                changes.push(format!(
+                    "third_party: {name} licenses changed: {before} -> {after}"
+                ));
+            } else {
+                changes.push(format!("third_party: {name} copyright changed"));
+            }
+        }
+        if pkg.comment != other.comment {
+            changes.push(format!("third_party: {name} comment changed"));
+        }
+        if pkg.chosen_rationale != other.chosen_rationale {
+            changes.push(format!("third_party: {name} rationale changed"));
+        }
+    }
+}
+
+fn describe_source(pkg: &Package) -> String {
+    serde_json::to_string(&pkg.source).unwrap_or_else(|_| format!("{:?}", pkg.source))
+}
+
+/// License expression of a package, including the chosen license if any
+pub(crate) fn describe_licenses(pkg: &Package) -> String {
+    let licenses: Vec<&str> = pkg
+        .licenses
+        .iter()
+        .map(|x| match x {
+            License::Unknown => "Unknown",
+            x => x.spdx_short(),
+        })
+        .collect();
+    let licenses = match &pkg.expression {
+        Some(expression) => expression.clone(),
+        None => licenses.join(pkg.combine.separator()),
+    };
+    match &pkg.chosen {
+        Some(chosen) => format!("{licenses} (chosen: {chosen})"),
+        None => licenses,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    const OLD: &str = r#"{"build_only":["cc"],"vendor":{},"third_party":{
+        "either":{"id":"either","source":"crates.io","combine":"OR","licenses":[{"ISC":{"copyright":"NotPresent"}},{"MIT":{"copyright":"NotPresent"}}]}},
+        "targets":{"app":{"subject":"App"}}}"#;
+
+    fn diff(old: &str, new: &str) -> (Vec<String>, bool) {
+        let dir = TempDir::new(&format!("diff-config-{}", new.len()));
+        let old = dir.write("old.json", old);
+        let new = dir.write("new.json", new);
+        let mut out = Vec::new();
+        let failed = diff_config(&old, &new, true, &mut out).is_err();
+        let lines = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|x| x.to_string())
+            .collect();
+        (lines, failed)
+    }
+
+    #[test]
+    fn identical_configurations_pass_the_check() {
+        assert_eq!(diff(OLD, OLD), (Vec::new(), false));
+    }
+
+    #[test]
+    fn reports_bucket_and_license_changes() {
+        let new = r#"{"build_only":[],"vendor":{"cc":{"url":"https://example.com"}},"third_party":{
+            "either":{"id":"either","source":"crates.io","comment":"approved","licenses":[{"MIT":{"copyright":"NotPresent"}}]}},
+            "targets":{"app":{"subject":"App"}}}"#;
+        let (changes, failed) = diff(OLD, new);
+
+        assert!(failed);
+        assert_eq!(
+            changes,
+            [
+                "build_only: removed cc",
+                "vendor: added cc",
+                "third_party: either licenses changed: ISC OR MIT -> MIT",
+                "third_party: either comment changed",
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_source_changes() {
+        let new = r#"{"build_only":["cc"],"vendor":{},"third_party":{
+            "either":{"id":"either","source":{"git":{"repo":"https://github.com/org/either","rev":"abc"}},"combine":"OR","licenses":[{"ISC":{"copyright":"NotPresent"}},{"MIT":{"copyright":"NotPresent"}}]}},
+            "targets":{"app":{"subject":"App"}}}"#;
+        let (changes, failed) = diff(OLD, new);
+
+        assert!(failed);
+        assert_eq!(
+            changes,
+            ["third_party: either source changed: \"crates.io\" -> {\"git\":{\"repo\":\"https://github.com/org/either\",\"rev\":\"abc\"}}"]
+        );
+    }
+
+    #[test]
+    fn reports_changes_of_the_top_level_settings() {
+        let new = OLD.replacen(
+            "\"targets\":{\"app\":{\"subject\":\"App\"}}",
+            r#""targets":{"app":{"subject":"The App"},"cli":{"subject":"CLI"}},
+            "copyright_placeholder":"no copyright","default_or_choice":"MIT","denied_licenses":["GPL-3.0-only"]"#,
+            1,
+        );
+        let (changes, failed) = diff(OLD, &new);
+
+        assert!(failed);
+        assert_eq!(
+            changes,
+            [
+                "targets: added cli",
+                "targets: app subject changed: App -> The App",
+                "copyright_placeholder changed: none -> \"no copyright\"",
+                "default_or_choice changed: none -> \"MIT\"",
+                "denied_licenses: added GPL-3.0-only",
+            ]
+        );
+    }
+}
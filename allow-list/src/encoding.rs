@@ -0,0 +1,147 @@
+use crate::cli::Encoding;
+use crate::warnings;
+use std::collections::BTreeSet;
+
+/// Writer that encodes the UTF-8 text of a report before passing it on
+pub(crate) struct EncodedWriter<W> {
+    inner: W,
+    encoding: Encoding,
+    started: bool,
+    /// trailing bytes of an incomplete UTF-8 sequence, kept until the next write
+    pending: Vec<u8>,
+    /// characters that could not be represented, each of which is reported once
+    unrepresentable: BTreeSet<char>,
+}
+
+impl<W> EncodedWriter<W>
+where
+    W: std::io::Write,
+{
+    pub(crate) fn new(inner: W, encoding: Encoding) -> Self {
+        Self {
+            inner,
+            encoding,
+            started: false,
+            pending: Vec::new(),
+            unrepresentable: BTreeSet::new(),
+        }
+    }
+}
+
+impl<W> std::io::Write for EncodedWriter<W>
+where
+    W: std::io::Write,
+{
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if !self.started {
+            self.started = true;
+            if self.encoding == Encoding::Utf8Bom {
+                self.inner.write_all("\u{feff}".as_bytes())?;
+            }
+        }
+        if self.encoding != Encoding::Latin1 {
+            self.inner.write_all(buf)?;
+            return Ok(buf.len());
+        }
+
+        self.pending.extend_from_slice(buf);
+        let valid = match std::str::from_utf8(&self.pending) {
+            Ok(text) => text.len(),
+            Err(err) if err.error_len().is_none() => err.valid_up_to(),
+            Err(err) => {
+                return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, err));
+            }
+        };
+        let text = String::from_utf8_lossy(&self.pending[..valid]).into_owned();
+        self.pending.drain(..valid);
+        let encoded = to_latin1(&text, &mut self.unrepresentable);
+        self.inner.write_all(&encoded)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Encode text as Latin-1, transliterating the common typographic characters
+/// and replacing any other character outside of Latin-1 with '?'
+fn to_latin1(text: &str, unrepresentable: &mut BTreeSet<char>) -> Vec<u8> {
+    let mut encoded = Vec::with_capacity(text.len());
+    for c in text.chars() {
+        if let Ok(byte) = u8::try_from(c) {
+            encoded.push(byte);
+            continue;
+        }
+        let replacement = match c {
+            '\u{feff}' => "",
+            '\u{2010}'..='\u{2015}' | '\u{2212}' => "-",
+            '\u{2018}' | '\u{2019}' | '\u{201a}' | '\u{2032}' => "'",
+            '\u{201c}' | '\u{201d}' | '\u{201e}' | '\u{2033}' => "\"",
+            '\u{2026}' => "...",
+            '\u{2022}' => "*",
+            '\u{2122}' => "(TM)",
+            _ => {
+                if unrepresentable.insert(c) {
+                    warnings::warn(format!(
+                        "'{c}' (U+{:04X}) cannot be represented in Latin-1 and was replaced with '?'",
+                        c as u32
+                    ));
+                }
+                "?"
+            }
+        };
+        encoded.extend_from_slice(replacement.as_bytes());
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn encode(encoding: Encoding, chunks: &[&[u8]]) -> Vec<u8> {
+        let mut out = Vec::new();
+        let mut w = EncodedWriter::new(&mut out, encoding);
+        for chunk in chunks {
+            w.write_all(chunk).unwrap();
+        }
+        out
+    }
+
+    #[test]
+    fn utf8_is_passed_through_with_an_optional_byte_order_mark() {
+        let text = "Copyright © 2023 “Zoë”\n".as_bytes();
+        assert_eq!(encode(Encoding::Utf8, &[text]), text);
+        assert_eq!(
+            encode(Encoding::Utf8Bom, &[text, text]),
+            [b"\xef\xbb\xbf".as_slice(), text, text].concat()
+        );
+        assert!(encode(Encoding::Utf8Bom, &[]).is_empty());
+    }
+
+    #[test]
+    fn latin1_transliterates_typographic_characters() {
+        assert_eq!(
+            encode(Encoding::Latin1, &["© Zoë – “AS IS”…\n".as_bytes()]),
+            b"\xa9 Zo\xeb - \"AS IS\"...\n"
+        );
+        assert_eq!(encode(Encoding::Latin1, &["Ĳ ✓ ✓".as_bytes()]), b"? ? ?");
+    }
+
+    #[test]
+    fn latin1_keeps_characters_split_across_writes() {
+        let text = "Zoë".as_bytes();
+        assert_eq!(
+            encode(Encoding::Latin1, &[&text[..3], &text[3..]]),
+            b"Zo\xeb"
+        );
+
+        let mut out = Vec::new();
+        let err = EncodedWriter::new(&mut out, Encoding::Latin1)
+            .write(b"\xff")
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,179 @@
+use crate::cli::{BomOptions, CheckOptions, ConfigOptions};
+use crate::diff_config::describe_licenses;
+use crate::licenses::{adopt_bom_licenses, check_components, extract_deps, read_bom};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Print why a crate is or is not included in the report generated for a BOM
+pub(crate) fn explain<W>(
+    bom_path: &Path,
+    config_options: &ConfigOptions,
+    crate_name: &str,
+    bom_options: &BomOptions,
+    checks: &CheckOptions,
+    mut w: W,
+) -> Result<(), anyhow::Error>
+where
+    W: std::io::Write,
+{
+    let mut config = config_options.load()?;
+    let bom = read_bom(bom_path, bom_options)?;
+
+    let versions: Vec<String> = bom
+        .components
+        .iter()
+        .flat_map(|x| x.0.iter())
+        .filter(|x| {
+            let name = x.name.to_string();
+            name == crate_name || (bom_options.ignore_case && name.eq_ignore_ascii_case(crate_name))
+        })
+        .filter_map(|x| x.version.as_ref().map(|x| x.to_string()))
+        .collect();
+
+    if versions.is_empty() {
+        writeln!(w, "{crate_name} was not found in the BOM")?;
+        writeln!(w, "decision: not in the report")?;
+        return Ok(());
+    }
+    writeln!(
+        w,
+        "{crate_name} was found in the BOM at version(s): {}",
+        versions.join(", ")
+    )?;
+
+    let name = if config.contains(crate_name) {
+        crate_name.to_string()
+    } else if let Some(key) = config
+        .find_ignore_case(crate_name)
+        .filter(|_| bom_options.ignore_case)
+    {
+        writeln!(
+            w,
+            "{crate_name} matches {key} in the configuration when ignoring case"
+        )?;
+        key.to_string()
+    } else {
+        crate_name.to_string()
+    };
+
+    if config.build_only.contains(&name) {
+        writeln!(w, "{name} is listed in build_only")?;
+        writeln!(
+            w,
+            "decision: excluded from the report because build-only dependencies are not distributed"
+        )?;
+        return Ok(());
+    }
+
+    if let Some(vendor) = config.vendor.get(&name) {
+        writeln!(w, "{name} is listed in vendor ({})", vendor.url)?;
+        writeln!(
+            w,
+            "decision: excluded from the report because vendor packages are distributed under the vendor license"
+        )?;
+        return Ok(());
+    }
+
+    let listed = config.third_party.contains_key(&name);
+    let mut components = extract_deps(bom, &config, bom_options)?;
+    adopt_bom_licenses(&mut config, &mut components, checks);
+
+    let pkg = match config.third_party.get(&name) {
+        Some(x) => x,
+        None => {
+            writeln!(w, "{name} is not listed in any bucket of the configuration")?;
+            writeln!(
+                w,
+                "decision: the report cannot be generated because {name} is not in the allow list"
+            )?;
+            return Ok(());
+        }
+    };
+    if listed {
+        writeln!(
+            w,
+            "{name} is listed in third_party with license(s): {}",
+            describe_licenses(pkg)
+        )?;
+    } else {
+        writeln!(
+            w,
+            "{name} is not in the configuration but declares license(s) in the BOM: {}",
+            describe_licenses(pkg)
+        )?;
+    }
+
+    let single: BTreeMap<String, _> = components
+        .into_iter()
+        .filter(|(key, _)| *key == name)
+        .collect();
+    let problems = check_components(&single, &config, checks);
+    if problems.is_empty() {
+        writeln!(w, "decision: included in the report")?;
+    } else {
+        writeln!(w, "decision: the report cannot be generated because of:")?;
+        for problem in problems {
+            writeln!(w, "  {problem}")?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{args, bom, config_options, TempDir, CONFIG};
+
+    fn explain_crate(crate_name: &str, check_args: &[&str]) -> String {
+        let dir = TempDir::new("explain");
+        let config = dir.write("config.json", CONFIG);
+        let bom = dir.write(
+            "bom.json",
+            &bom(&[("cc", "1.0.83"), ("serde", "1.0.188"), ("libc", "0.2.150")]),
+        );
+        let mut out = Vec::new();
+        explain(
+            &bom,
+            &config_options(&[&config]),
+            crate_name,
+            &BomOptions::default(),
+            &args(check_args),
+            &mut out,
+        )
+        .unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn build_only_crates_are_excluded() {
+        assert_eq!(
+            explain_crate("cc", &[]),
+            "cc was found in the BOM at version(s): 1.0.83\n\
+             cc is listed in build_only\n\
+             decision: excluded from the report because build-only dependencies are not distributed\n"
+        );
+    }
+
+    #[test]
+    fn third_party_crates_are_included_unless_a_check_fails() {
+        assert_eq!(
+            explain_crate("serde", &[]),
+            "serde was found in the BOM at version(s): 1.0.188\n\
+             serde is listed in third_party with license(s): MIT\n\
+             decision: included in the report\n"
+        );
+        assert!(explain_crate("libc", &["--pedantic"]).ends_with(
+            "decision: the report cannot be generated because of:\n  \
+             No copyright statement was provided for the MIT license of libc\n"
+        ));
+    }
+
+    #[test]
+    fn missing_crates_are_not_in_the_report() {
+        assert_eq!(
+            explain_crate("tokio", &[]),
+            "tokio was not found in the BOM\ndecision: not in the report\n"
+        );
+    }
+}
@@ -0,0 +1,306 @@
+use crate::config::Combine;
+
+/// A parsed SPDX license expression, e.g. `(MIT OR Apache-2.0) AND Unicode-DFS-2016`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Expression {
+    /// a single license, including its exception if any, e.g. `GPL-3.0-only WITH GCC-exception-3.1`
+    License(String),
+    /// licenses or groups that are combined with AND or OR
+    Compound(Combine, Vec<Expression>),
+}
+
+impl Expression {
+    /// Parse an expression, where AND binds tighter than OR and parentheses group terms
+    pub(crate) fn parse(text: &str) -> Result<Self, anyhow::Error> {
+        let spaced = text.replace('(', " ( ").replace(')', " ) ");
+        let tokens: Vec<&str> = spaced.split_whitespace().collect();
+        let mut pos = 0;
+        let expression = parse_or(&tokens, &mut pos)?;
+        match tokens.get(pos) {
+            None => Ok(expression),
+            Some(token) => Err(anyhow::Error::msg(format!(
+                "Unexpected '{token}' in license expression '{text}'"
+            ))),
+        }
+    }
+
+    /// Combine expressions, merging nested groups of the same kind
+    pub(crate) fn combine(combine: Combine, items: Vec<Expression>) -> Self {
+        let mut flat = Vec::new();
+        for item in items {
+            match item {
+                Expression::Compound(inner, children) if inner == combine => flat.extend(children),
+                item => flat.push(item),
+            }
+        }
+        if flat.len() == 1 {
+            flat.remove(0)
+        } else {
+            Expression::Compound(combine, flat)
+        }
+    }
+
+    /// SPDX ids of every license in the expression, without duplicates, in the order they appear
+    pub(crate) fn ids(&self) -> Vec<&str> {
+        let mut ids = Vec::new();
+        self.visit(&mut |id| {
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        });
+        ids
+    }
+
+    /// SPDX ids of the licenses whose obligations are fulfilled, i.e. the first alternative of every OR
+    pub(crate) fn first_alternative(&self) -> Vec<&str> {
+        match self {
+            Expression::License(id) => vec![id.as_str()],
+            Expression::Compound(Combine::Or, items) => items
+                .first()
+                .map(|x| x.first_alternative())
+                .unwrap_or_default(),
+            Expression::Compound(Combine::And, items) => {
+                let mut ids = Vec::new();
+                for id in items.iter().flat_map(|x| x.first_alternative()) {
+                    if !ids.contains(&id) {
+                        ids.push(id);
+                    }
+                }
+                ids
+            }
+        }
+    }
+
+    /// True if the expression only combines single licenses, i.e. it fits in a list of licenses and a `Combine`
+    pub(crate) fn is_flat(&self) -> bool {
+        match self {
+            Expression::License(_) => true,
+            Expression::Compound(_, items) => {
+                items.iter().all(|x| matches!(x, Expression::License(_)))
+            }
+        }
+    }
+
+    /// Equivalent expression whose groups list their items in a canonical order, for comparisons
+    pub(crate) fn normalized(&self) -> Self {
+        match self {
+            Expression::License(id) => Expression::License(id.to_ascii_lowercase()),
+            Expression::Compound(combine, items) => {
+                let mut items: Vec<Expression> = items.iter().map(|x| x.normalized()).collect();
+                items.sort_by_key(|x| x.to_string());
+                items.dedup();
+                Expression::combine(*combine, items)
+            }
+        }
+    }
+
+    /// Evaluate the expression bottom-up, mapping each license with `leaf` and each group with `group`
+    pub(crate) fn fold<T>(
+        &self,
+        leaf: &mut impl FnMut(&str) -> T,
+        group: &impl Fn(Combine, Vec<T>) -> T,
+    ) -> T {
+        match self {
+            Expression::License(id) => leaf(id),
+            Expression::Compound(combine, items) => {
+                let values = items.iter().map(|x| x.fold(leaf, group)).collect();
+                group(*combine, values)
+            }
+        }
+    }
+
+    fn visit<'a>(&'a self, f: &mut impl FnMut(&'a str)) {
+        match self {
+            Expression::License(id) => f(id),
+            Expression::Compound(_, items) => {
+                for item in items {
+                    item.visit(f);
+                }
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for Expression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Expression::License(id) => f.write_str(id),
+            Expression::Compound(combine, items) => {
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(combine.separator())?;
+                    }
+                    // nested groups are always parenthesized so readers need not know the precedence
+                    match item {
+                        Expression::Compound(..) => write!(f, "({item})")?,
+                        Expression::License(_) => write!(f, "{item}")?,
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+fn parse_or(tokens: &[&str], pos: &mut usize) -> Result<Expression, anyhow::Error> {
+    let mut items = vec![parse_and(tokens, pos)?];
+    while tokens.get(*pos).is_some_and(|x| is_operator(x, "OR")) {
+        *pos += 1;
+        items.push(parse_and(tokens, pos)?);
+    }
+    Ok(Expression::combine(Combine::Or, items))
+}
+
+fn parse_and(tokens: &[&str], pos: &mut usize) -> Result<Expression, anyhow::Error> {
+    let mut items = vec![parse_term(tokens, pos)?];
+    while tokens.get(*pos).is_some_and(|x| is_operator(x, "AND")) {
+        *pos += 1;
+        items.push(parse_term(tokens, pos)?);
+    }
+    Ok(Expression::combine(Combine::And, items))
+}
+
+fn parse_term(tokens: &[&str], pos: &mut usize) -> Result<Expression, anyhow::Error> {
+    let token = tokens
+        .get(*pos)
+        .ok_or_else(|| anyhow::Error::msg("License expression ends unexpectedly"))?;
+    *pos += 1;
+
+    if *token == "(" {
+        let inner = parse_or(tokens, pos)?;
+        if tokens.get(*pos) != Some(&")") {
+            return Err(anyhow::Error::msg("Missing ')' in license expression"));
+        }
+        *pos += 1;
+        return Ok(inner);
+    }
+    if *token == ")" || ["AND", "OR", "WITH"].iter().any(|x| is_operator(token, x)) {
+        return Err(anyhow::Error::msg(format!(
+            "Expected a license but found '{token}' in license expression"
+        )));
+    }
+
+    if tokens.get(*pos).is_some_and(|x| is_operator(x, "WITH")) {
+        let exception = tokens
+            .get(*pos + 1)
+            .filter(|x| **x != "(" && **x != ")")
+            .ok_or_else(|| anyhow::Error::msg(format!("Missing exception after {token} WITH")))?;
+        *pos += 2;
+        return Ok(Expression::License(format!("{token} WITH {exception}")));
+    }
+
+    Ok(Expression::License(token.to_string()))
+}
+
+/// SPDX operators are either all upper or all lower case
+fn is_operator(token: &str, operator: &str) -> bool {
+    token == operator || token == operator.to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn license(id: &str) -> Expression {
+        Expression::License(id.to_string())
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        assert_eq!(
+            Expression::parse("MIT OR Apache-2.0 AND ISC").unwrap(),
+            Expression::Compound(
+                Combine::Or,
+                vec![
+                    license("MIT"),
+                    Expression::Compound(Combine::And, vec![license("Apache-2.0"), license("ISC")]),
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn parentheses_group_terms() {
+        let expression = Expression::parse("(MIT OR Apache-2.0) AND Unicode-DFS-2016").unwrap();
+        assert_eq!(
+            expression,
+            Expression::Compound(
+                Combine::And,
+                vec![
+                    Expression::Compound(Combine::Or, vec![license("MIT"), license("Apache-2.0")]),
+                    license("Unicode-DFS-2016"),
+                ]
+            )
+        );
+        assert_eq!(
+            expression.to_string(),
+            "(MIT OR Apache-2.0) AND Unicode-DFS-2016"
+        );
+    }
+
+    #[test]
+    fn nested_groups_of_the_same_kind_are_merged() {
+        assert_eq!(
+            Expression::parse("MIT OR (Apache-2.0 OR ISC)")
+                .unwrap()
+                .to_string(),
+            "MIT OR Apache-2.0 OR ISC"
+        );
+        assert_eq!(Expression::parse("((MIT))").unwrap(), license("MIT"));
+    }
+
+    #[test]
+    fn exceptions_and_lower_case_operators() {
+        assert_eq!(
+            Expression::parse("GPL-3.0-only with GCC-exception-3.1 or MIT").unwrap(),
+            Expression::Compound(
+                Combine::Or,
+                vec![
+                    license("GPL-3.0-only WITH GCC-exception-3.1"),
+                    license("MIT")
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn invalid_expressions_are_errors() {
+        let error = |text: &str| Expression::parse(text).unwrap_err().to_string();
+        assert_eq!(error("MIT OR"), "License expression ends unexpectedly");
+        assert_eq!(error("(MIT OR ISC"), "Missing ')' in license expression");
+        assert_eq!(
+            error("MIT ISC"),
+            "Unexpected 'ISC' in license expression 'MIT ISC'"
+        );
+        assert_eq!(
+            error("AND MIT"),
+            "Expected a license but found 'AND' in license expression"
+        );
+        assert_eq!(
+            error("GPL-3.0-only WITH"),
+            "Missing exception after GPL-3.0-only WITH"
+        );
+    }
+
+    #[test]
+    fn first_alternative_of_every_or_is_fulfilled() {
+        let expression = Expression::parse("(MIT OR Apache-2.0) AND (ISC OR MIT)").unwrap();
+        assert_eq!(expression.ids(), ["MIT", "Apache-2.0", "ISC"]);
+        assert_eq!(expression.first_alternative(), ["MIT", "ISC"]);
+        assert!(!expression.is_flat());
+        assert!(Expression::parse("MIT OR ISC").unwrap().is_flat());
+    }
+
+    #[test]
+    fn normalized_ignores_order_and_case() {
+        let a = Expression::parse("MIT OR Apache-2.0").unwrap();
+        let b = Expression::parse("apache-2.0 OR mit OR MIT").unwrap();
+        assert_eq!(a.normalized(), b.normalized());
+        assert_ne!(
+            a.normalized(),
+            Expression::parse("MIT AND Apache-2.0")
+                .unwrap()
+                .normalized()
+        );
+    }
+}
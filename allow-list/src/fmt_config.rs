@@ -0,0 +1,136 @@
+use crate::config::Config;
+use std::path::Path;
+
+/// Rewrite a configuration file in canonical form, i.e. pretty-printed with the fields in the order
+/// of the configuration structures and the crates sorted by name
+///
+/// If `check` is set, the file is left untouched and an error is returned if it is not canonical.
+/// A file that cannot be rewritten without losing or changing some of its content is left untouched
+/// and reported as an error, e.g. if it has keys that the configuration does not know.
+pub(crate) fn fmt_config<W>(config_path: &Path, check: bool, mut w: W) -> Result<(), anyhow::Error>
+where
+    W: std::io::Write,
+{
+    let original = std::fs::read_to_string(config_path)?;
+    let config: Config = serde_json::from_str(&original)?;
+    config.validate()?;
+
+    let mut changed = Vec::new();
+    differences(
+        &serde_json::from_str(&original)?,
+        &serde_json::to_value(&config)?,
+        "",
+        &mut changed,
+    );
+    if !changed.is_empty() {
+        return Err(anyhow::Error::msg(format!(
+            "{} cannot be formatted without dropping or changing: {}",
+            config_path.display(),
+            changed.join(", ")
+        )));
+    }
+
+    let mut formatted = serde_json::to_string_pretty(&config)?;
+    formatted.push('\n');
+
+    if formatted == original {
+        writeln!(w, "{} is already formatted", config_path.display())?;
+        return Ok(());
+    }
+
+    if check {
+        return Err(anyhow::Error::msg(format!(
+            "{} is not formatted, run fmt-config to fix it",
+            config_path.display()
+        )));
+    }
+
+    std::fs::write(config_path, formatted)?;
+    writeln!(w, "formatted {}", config_path.display())?;
+
+    Ok(())
+}
+
+/// Collect the paths (e.g. `third_party.foo._comment`) at which two JSON values differ, ignoring the order of keys
+fn differences(
+    original: &serde_json::Value,
+    formatted: &serde_json::Value,
+    path: &str,
+    changed: &mut Vec<String>,
+) {
+    let join = |key: &str| match path {
+        "" => key.to_string(),
+        path => format!("{path}.{key}"),
+    };
+    match (original, formatted) {
+        (serde_json::Value::Object(a), serde_json::Value::Object(b)) => {
+            for (key, value) in a.iter() {
+                match b.get(key) {
+                    Some(other) => differences(value, other, &join(key), changed),
+                    None => changed.push(join(key)),
+                }
+            }
+            for key in b.keys().filter(|x| !a.contains_key(*x)) {
+                changed.push(join(key));
+            }
+        }
+        (serde_json::Value::Array(a), serde_json::Value::Array(b)) if a.len() == b.len() => {
+            for (i, (a, b)) in a.iter().zip(b).enumerate() {
+                differences(a, b, &join(&i.to_string()), changed);
+            }
+        }
+        (a, b) if a != b => changed.push(if path.is_empty() { "." } else { path }.to_string()),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    const MESSY: &str = r#"{"third_party":{"serde":{"licenses":[{"MIT":{"copyright":"NotPresent"}}],"source":"crates.io","id":"serde"}},
+        "vendor":{},"build_only":["cc"]}"#;
+
+    #[test]
+    fn formats_a_configuration() {
+        let dir = TempDir::new("fmt-config");
+        let path = dir.write("config.json", MESSY);
+
+        let mut out = Vec::new();
+        assert!(fmt_config(&path, true, &mut out).is_err());
+        fmt_config(&path, false, &mut out).unwrap();
+        let formatted = std::fs::read_to_string(&path).unwrap();
+        assert!(formatted.starts_with("{\n  \"build_only\": [\n    \"cc\"\n  ],\n"));
+
+        fmt_config(&path, true, &mut out).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), formatted);
+    }
+
+    #[test]
+    fn refuses_to_drop_unknown_keys() {
+        let dir = TempDir::new("fmt-config-unknown");
+        let content = MESSY
+            .replacen(
+                "{\"third_party\"",
+                "{\"_comment\":\"reviewed\",\"third_party\"",
+                1,
+            )
+            .replacen(
+                "\"id\":\"serde\"",
+                "\"id\":\"serde\",\"combine\":\"AND\"",
+                1,
+            );
+        let path = dir.write("config.json", &content);
+
+        let err = fmt_config(&path, false, Vec::new()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "{} cannot be formatted without dropping or changing: _comment, third_party.serde.combine",
+                path.display()
+            )
+        );
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), content);
+    }
+}
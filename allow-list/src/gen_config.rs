@@ -0,0 +1,172 @@
+use crate::cli::BomOptions;
+use crate::config::{Combine, Config, License, Package, Source};
+use crate::licenses::{git_url, read_bom};
+use cyclonedx_bom::models::component::{Component, Scope};
+use packageurl::PackageUrl;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+use std::str::FromStr;
+
+/// Generate a skeleton configuration file listing every component in a BOM
+///
+/// If `merge_path` is provided, the entries of that configuration are kept as they are
+/// and only the components it does not list are added
+pub(crate) fn gen_config_from_bom<W>(
+    bom_path: &Path,
+    merge_path: Option<&Path>,
+    w: W,
+) -> Result<(), anyhow::Error>
+where
+    W: std::io::Write,
+{
+    let bom = read_bom(bom_path, &BomOptions::default())?;
+
+    let components = &bom
+        .components
+        .ok_or_else(|| anyhow::Error::msg("required field 'components' is 'None'"))?
+        .0;
+
+    let mut build_only = BTreeSet::new();
+    let mut third_party = BTreeMap::new();
+
+    for component in components.iter() {
+        let name = component.name.to_string();
+        if let Some(Scope::Excluded) = component.scope {
+            build_only.insert(name);
+            continue;
+        }
+
+        let source = source_of(component)?;
+        third_party.insert(
+            name.clone(),
+            Package {
+                id: name,
+                source,
+                licenses: vec![License::Unknown],
+                combine: Combine::And,
+                expression: None,
+                comment: None,
+                chosen: None,
+                chosen_rationale: None,
+            },
+        );
+    }
+
+    // a crate that is distributed in any version must be fully licensed
+    build_only.retain(|x| !third_party.contains_key(x));
+
+    let config = match merge_path {
+        Some(path) => {
+            // read as written, so that the default OR choices are not recorded in the packages
+            let mut config = Config::read(path)?;
+            for name in build_only {
+                if !config.contains(&name) {
+                    config.build_only.insert(name);
+                }
+            }
+            for (name, pkg) in third_party {
+                if !config.contains(&name) {
+                    config.third_party.insert(name, pkg);
+                }
+            }
+            config
+        }
+        None => Config {
+            build_only,
+            vendor: BTreeMap::new(),
+            third_party,
+            targets: BTreeMap::new(),
+            copyright_placeholder: None,
+            default_or_choice: None,
+            denied_licenses: BTreeSet::new(),
+        },
+    };
+
+    serde_json::to_writer_pretty(w, &config)?;
+
+    Ok(())
+}
+
+/// Determine where a component came from using its purl
+fn source_of(component: &Component) -> Result<Source, anyhow::Error> {
+    let purl = match &component.purl {
+        Some(x) => x.to_string(),
+        None => return Ok(Source::CratesIo),
+    };
+
+    let purl = PackageUrl::from_str(&purl)
+        .map_err(|err| anyhow::Error::msg(format!("Invalid purl {purl}: {err}")))?;
+
+    match purl.ty() {
+        "cargo" => Ok(git_url(component)
+            .map(|x| Source::from_vcs_url(&x))
+            .unwrap_or(Source::CratesIo)),
+        other => Err(anyhow::Error::msg(format!(
+            "Unsupported purl type '{other}' in component {}",
+            component.name
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{bom_with, component, TempDir};
+
+    fn gen(dir: &TempDir, merge: Option<&Path>) -> serde_json::Value {
+        let mut cc = component("cc", "1.0.83");
+        cc["scope"] = "excluded".into();
+        let mut fork = component("fork", "0.1.0");
+        fork["purl"] =
+            "pkg:cargo/fork@0.1.0?vcs_url=git%2Bhttps://github.com/org/fork%23abc123".into();
+        let bom = dir.write(
+            "bom.json",
+            &bom_with(vec![cc, component("libc", "0.2.150"), fork]),
+        );
+
+        let mut out = Vec::new();
+        gen_config_from_bom(&bom, merge, &mut out).unwrap();
+        serde_json::from_slice(&out).unwrap()
+    }
+
+    #[test]
+    fn lists_every_component_of_the_bom() {
+        let dir = TempDir::new("gen-config");
+        let config = gen(&dir, None);
+
+        assert_eq!(config["build_only"], serde_json::json!(["cc"]));
+        assert_eq!(
+            config["third_party"]["libc"],
+            serde_json::json!({"id": "libc", "source": "crates.io", "licenses": ["Unknown"]})
+        );
+        assert_eq!(
+            config["third_party"]["fork"]["source"],
+            serde_json::json!({"git": {"repo": "https://github.com/org/fork", "rev": "abc123"}})
+        );
+    }
+
+    #[test]
+    fn merge_keeps_the_existing_entries() {
+        let dir = TempDir::new("gen-config-merge");
+        let existing = dir.write(
+            "config.json",
+            r#"{"build_only":[],"vendor":{},"default_or_choice":"MIT","third_party":{
+                "libc":{"id":"libc","source":"crates.io","licenses":[{"MIT":{"copyright":"NotPresent"}}]},
+                "either":{"id":"either","source":"crates.io","combine":"OR","licenses":[{"Apache2":{}},{"MIT":{"copyright":"NotPresent"}}]}}}"#,
+        );
+        let config = gen(&dir, Some(&existing));
+
+        assert_eq!(config["default_or_choice"], "MIT");
+        assert_eq!(config["build_only"], serde_json::json!(["cc"]));
+        assert_eq!(
+            config["third_party"]["libc"]["licenses"],
+            serde_json::json!([{"MIT": {"copyright": "NotPresent"}}])
+        );
+        assert_eq!(
+            config["third_party"]["fork"]["licenses"],
+            serde_json::json!(["Unknown"])
+        );
+        // the default OR choice is not written into the packages
+        assert!(config["third_party"]["either"].get("chosen").is_none());
+    }
+}
@@ -0,0 +1,172 @@
+use crate::report::{LicenseText, Notice, Report};
+
+/// Write the header and the per-crate information as an HTML fragment
+pub(crate) fn write_summary<W>(report: &Report, w: &mut W, location: &str) -> std::io::Result<()>
+where
+    W: std::io::Write,
+{
+    writeln!(w, "<h1>Licenses</h1>")?;
+    writeln!(w, "<p>{}</p>", escape(&report.intro()))?;
+    writeln!(w, "<ul>")?;
+    for (spdx, info) in report.licenses.iter() {
+        write!(
+            w,
+            "<li>{} - {}",
+            escape(&report.license_label(spdx)),
+            link(info.url)
+        )?;
+        if let Some(note) = info.note {
+            write!(w, " (note: {})", escape(note))?;
+        }
+        if let Some(obligations) = report.obligations(spdx) {
+            write!(w, " (obligations: {})", escape(&obligations))?;
+        }
+        writeln!(w, "</li>")?;
+    }
+    writeln!(w, "</ul>")?;
+    writeln!(w, "<p>{}</p>", escape(&report.copies_sentence(location)))?;
+
+    writeln!(w, "<h1>Crates</h1>")?;
+    for entry in report.crates.iter() {
+        writeln!(w, "<h2>{}</h2>", escape(entry.id))?;
+        writeln!(w, "<dl>")?;
+        field(w, "version(s)", &escape(&entry.versions.join(", ")))?;
+        if let Some(targets) = &entry.targets {
+            field(w, "target(s)", &escape(targets))?;
+        }
+        for (target, versions) in entry.target_versions.iter() {
+            field(
+                w,
+                &format!("version(s) in {}", escape(target)),
+                &escape(versions),
+            )?;
+        }
+        field(w, "url", &link(&entry.url))?;
+        field(w, "license(s)", &escape(&entry.expression))?;
+        if let Some(rationale) = entry.rationale {
+            field(w, "rationale", &escape(rationale))?;
+        }
+        if let Some(note) = entry.note {
+            field(w, "note", &escape(note))?;
+        }
+        for author in entry.attribution.iter() {
+            field(w, "attribution", &escape(author))?;
+        }
+        writeln!(w, "</dl>")?;
+
+        if !entry.copyright.is_empty() {
+            let lines: Vec<String> = entry.copyright.iter().map(|x| escape(x)).collect();
+            writeln!(w, "<p>{}</p>", lines.join("<br>"))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Write the license texts as preformatted blocks
+pub(crate) fn write_texts<W>(w: &mut W, texts: &[(&str, LicenseText)]) -> std::io::Result<()>
+where
+    W: std::io::Write,
+{
+    writeln!(w, "<h1>License texts</h1>")?;
+    for (spdx, text) in texts {
+        writeln!(w, "<h2>{}</h2>", escape(spdx))?;
+        match text {
+            LicenseText::Inline(text) => writeln!(w, "<pre>{}</pre>", escape(text.trim_end()))?,
+            LicenseText::File(path) => writeln!(
+                w,
+                "<p>See file: <code>{}</code></p>",
+                escape(&path.display().to_string())
+            )?,
+        }
+    }
+    Ok(())
+}
+
+/// Write the NOTICE files as preformatted blocks after the license texts
+pub(crate) fn write_notices<W>(w: &mut W, notices: &[Notice]) -> std::io::Result<()>
+where
+    W: std::io::Write,
+{
+    if notices.is_empty() {
+        return Ok(());
+    }
+    writeln!(w, "<h1>NOTICE</h1>")?;
+    for notice in notices {
+        writeln!(
+            w,
+            "<h2>NOTICE of {}</h2>",
+            escape(&notice.crates.join(", "))
+        )?;
+        writeln!(w, "<pre>{}</pre>", escape(&notice.lines.join("\n")))?;
+    }
+    Ok(())
+}
+
+/// Write an entry of a description list
+fn field<W>(w: &mut W, name: &str, value: &str) -> std::io::Result<()>
+where
+    W: std::io::Write,
+{
+    writeln!(w, "<dt>{name}</dt><dd>{value}</dd>")
+}
+
+/// Anchor whose text is the URL itself
+fn link(url: &str) -> String {
+    let url = escape(url);
+    format!("<a href=\"{url}\">{url}</a>")
+}
+
+/// Escape the characters that HTML would interpret as markup, in text and in attribute values
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{bom, gen_report, CONFIG};
+
+    #[test]
+    fn escapes_markup_and_attribute_quotes() {
+        assert_eq!(escape("MIT"), "MIT");
+        assert_eq!(
+            escape("<a href=\"x\">Tom & Jerry's</a>"),
+            "&lt;a href=&quot;x&quot;&gt;Tom &amp; Jerry&#39;s&lt;/a&gt;"
+        );
+        assert_eq!(
+            link("https://example.com/?a=1&b=2"),
+            "<a href=\"https://example.com/?a=1&amp;b=2\">https://example.com/?a=1&amp;b=2</a>"
+        );
+    }
+
+    #[test]
+    fn report_is_structured_as_html() {
+        let bom = bom(&[("serde", "1.0.188"), ("ring", "0.17.5")]);
+        let report = gen_report(CONFIG, &bom, &["--format", "html"]).unwrap();
+        assert!(report.starts_with("<h1>Licenses</h1>\n<p>This distribution"));
+        assert!(report.contains(
+            "<li>MIT - <a href=\"https://spdx.org/licenses/MIT.html\">https://spdx.org/licenses/MIT.html</a></li>\n"
+        ));
+        assert!(report.contains("<h2>ring</h2>\n<dl>\n<dt>version(s)</dt><dd>0.17.5</dd>\n"));
+        assert!(report.contains("<dt>license(s)</dt><dd>OpenSSL AND ISC</dd>\n</dl>\n<p>Copyright 2015-2016 Brian Smith.</p>\n"));
+        assert!(report.contains("<h2>MIT</h2>\n<pre>===="));
+        assert!(report.contains("&quot;AS IS&quot;"));
+        assert!(!report.contains("\"AS IS\""));
+        assert_eq!(
+            report.matches("<pre>").count(),
+            report.matches("</pre>").count()
+        );
+    }
+}
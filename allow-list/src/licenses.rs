@@ -1,25 +1,126 @@
-use crate::config::{Config, LicenseInfo};
+use crate::cli::{BomOptions, CheckOptions, ConfigOptions, DirOptions, Format, ReportOptions};
+use crate::compat;
+use crate::config::{Combine, Config, Copyright, License, Package, Source};
+use crate::encoding::EncodedWriter;
+use crate::expression::Expression;
+use crate::report::Report;
+use crate::warnings;
+use cyclonedx_bom::models::component::{Component, Scope};
+use cyclonedx_bom::models::license::{LicenseChoice, LicenseIdentifier};
 use cyclonedx_bom::prelude::Bom;
+use packageurl::PackageUrl;
 use semver::Version;
+use serde::Serialize;
 use std::collections::btree_map::Entry;
-use std::collections::BTreeMap;
-use std::path::Path;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// A distributed dependency extracted from a BOM
+pub(crate) struct Dependency {
+    /// distinct versions of the dependency, in the order they appear in the BOM
+    ///
+    /// Build metadata is dropped, so `1.0.0` and `1.0.0+build.5` are the same version
+    pub(crate) versions: Vec<Version>,
+    /// author (or publisher) of the component as declared in the BOM
+    pub(crate) authors: BTreeSet<String>,
+    /// git repositories the dependency was built from, according to the purl
+    pub(crate) git_urls: BTreeSet<String>,
+    /// entry built from the licenses declared in the BOM, if the crate is not in the configuration
+    pub(crate) declared: Option<Package>,
+    /// targets the dependency applies to, if they are reported
+    pub(crate) targets: Option<Targets>,
+    /// versions of the dependency in each target, if they are reported
+    pub(crate) target_versions: Option<BTreeMap<String, Vec<Version>>>,
+}
+
+/// Targets (i.e. directories of BOMs) a dependency was found in
+pub(crate) enum Targets {
+    /// the dependency was found in every target
+    All,
+    /// the dependency was only found in these targets
+    Only(BTreeSet<String>),
+}
 
 /// Generate a license summary file from a build log and configuration file
 pub(crate) fn gen_licenses<W>(
     bom_path: &Path,
-    config_path: &Path,
+    config_options: &ConfigOptions,
+    bom_options: &BomOptions,
+    options: &ReportOptions,
     w: W,
 ) -> Result<(), anyhow::Error>
 where
     W: std::io::Write,
 {
-    let bom = Bom::parse_from_json_v1_4(std::fs::File::open(bom_path)?)?;
-    let config: Config = serde_json::from_reader(std::fs::File::open(config_path)?)?;
+    let bom = read_bom(bom_path, bom_options)?;
+    let mut config = config_options.load()?;
+
+    let mut components = extract_deps(bom, &config, bom_options)?;
+    if let Some(path) = &options.baseline {
+        subtract_baseline(&mut components, path, &config, bom_options)?;
+    }
+    omit_old_versions(&mut components, &options.since_version);
+    adopt_bom_licenses(&mut config, &mut components, &options.checks);
+
+    gen_licenses_for(&components, &config, None, options, w)?;
+
+    Ok(())
+}
+
+/// Generate a license summary file for each target of the configuration from the BOM in its directory
+pub(crate) fn gen_all(
+    list_dir: &Path,
+    bom_file: &str,
+    config_options: &ConfigOptions,
+    output_dir: &Path,
+    bom_options: &BomOptions,
+    options: &ReportOptions,
+) -> Result<(), anyhow::Error> {
+    if options.summary_output.is_some() || options.attributions.is_some() || options.csv.is_some() {
+        return Err(anyhow::Error::msg(
+            "--summary-output, --text-output, --attributions and --csv write a single file and cannot be used with gen-all",
+        ));
+    }
+
+    let targets = config_options.load()?.targets;
+    if targets.is_empty() {
+        return Err(anyhow::Error::msg(format!(
+            "{} does not define any targets",
+            config_options.describe()
+        )));
+    }
+
+    let extension = match options.format {
+        Format::Text => "txt",
+        Format::Rst => "rst",
+        Format::Html => "html",
+        Format::Json => "json",
+    };
 
-    let components = extract_deps(bom, &config)?;
+    for (name, target) in targets.iter() {
+        // each target adopts the licenses declared in its own BOM only
+        let mut config = config_options.load()?;
+        let bom = read_bom(&list_dir.join(name).join(bom_file), bom_options)?;
+        let mut components = extract_deps(bom, &config, bom_options)?;
+        if let Some(path) = &options.baseline {
+            subtract_baseline(&mut components, path, &config, bom_options)?;
+        }
+        omit_old_versions(&mut components, &options.since_version);
+        adopt_bom_licenses(&mut config, &mut components, &options.checks);
 
-    gen_licenses_for(&components, &config, w)?;
+        let path = output_dir.join(format!("{name}.{extension}"));
+        warnings::info(format!(
+            "writing the notice of {name} to {}",
+            path.display()
+        ));
+        crate::write_output(Some(&path), |w| {
+            gen_licenses_for(&components, &config, Some(&target.subject), options, w)
+        })
+        .map_err(|err| {
+            anyhow::Error::msg(format!("Unable to generate the notice of {name}: {err}"))
+        })?;
+    }
 
     Ok(())
 }
@@ -28,123 +129,846 @@ where
 pub(crate) fn gen_licenses_in_dirs<W>(
     list_dir: &Path,
     bom_file: &str,
-    config_path: &Path,
+    config_options: &ConfigOptions,
+    dir_options: &DirOptions,
+    bom_options: &BomOptions,
+    options: &ReportOptions,
     w: W,
 ) -> Result<(), anyhow::Error>
 where
     W: std::io::Write,
 {
-    let config: Config = serde_json::from_reader(std::fs::File::open(config_path)?)?;
+    let mut config = config_options.load()?;
     let mut components = BTreeMap::new();
 
+    let mut dirs = Vec::new();
     for item in std::fs::read_dir(list_dir)? {
         let item = item?;
         if item.file_type()?.is_dir() {
-            let bom = Bom::parse_from_json_v1_4(std::fs::File::open(item.path().join(bom_file))?)?;
-            for (name, versions) in extract_deps(bom, &config)? {
-                match components.entry(name.clone()) {
-                    Entry::Vacant(x) => {
-                        x.insert(versions);
-                    }
-                    Entry::Occupied(occ) => {
-                        if occ.get().as_slice() != versions.as_slice() {
-                            return Err(anyhow::Error::msg(format!(
-                                "Version mismatch in {name}: {:?} vs {:?}",
-                                occ.get().as_slice(),
-                                versions.as_slice()
-                            )));
+            dirs.push(item.path());
+        }
+    }
+    // the order of the directories determines the order of merged versions
+    dirs.sort();
+
+    if dir_options.require_all {
+        let missing: Vec<String> = dirs
+            .iter()
+            .filter(|dir| !dir.join(bom_file).is_file())
+            .map(|dir| dir.display().to_string())
+            .collect();
+        if !missing.is_empty() {
+            return Err(anyhow::Error::msg(format!(
+                "{bom_file} is missing from the following directories: {}",
+                missing.join(", ")
+            )));
+        }
+    }
+
+    let boms = read_boms(&dirs, bom_file, bom_options)?;
+
+    let mut found_in: BTreeMap<String, BTreeMap<String, Vec<Version>>> = BTreeMap::new();
+    for (dir, bom) in dirs.iter().zip(boms) {
+        let target = dir
+            .file_name()
+            .map(|x| x.to_string_lossy().to_string())
+            .unwrap_or_default();
+        for (name, dep) in extract_deps(bom, &config, bom_options)? {
+            found_in
+                .entry(name.clone())
+                .or_default()
+                .insert(target.clone(), dep.versions.clone());
+            match components.entry(name.clone()) {
+                Entry::Vacant(x) => {
+                    x.insert(dep);
+                }
+                Entry::Occupied(occ) => {
+                    let existing: &mut Dependency = occ.into_mut();
+                    if existing.versions.as_slice() != dep.versions.as_slice() {
+                        let mismatch = format!(
+                            "Version mismatch in {name}: {:?} vs {:?}",
+                            existing.versions.as_slice(),
+                            dep.versions.as_slice()
+                        );
+                        if dir_options.warn_version_skew && !dir_options.show_target_versions {
+                            warnings::warn(format!("{mismatch}, merging them"));
+                        } else if !dir_options.show_target_versions {
+                            return Err(anyhow::Error::msg(mismatch));
                         }
+                        for version in dep.versions {
+                            if !existing.versions.contains(&version) {
+                                existing.versions.push(version);
+                            }
+                        }
+                    }
+                    existing.authors.extend(dep.authors);
+                    existing.git_urls.extend(dep.git_urls);
+                    if existing.declared.is_none() {
+                        existing.declared = dep.declared;
                     }
                 }
             }
         }
     }
+    if dir_options.warn_version_skew {
+        for skew in version_skew(&components, dir_options.max_versions) {
+            warnings::warn(skew);
+        }
+    }
+    if let Some(path) = &options.baseline {
+        subtract_baseline(&mut components, path, &config, bom_options)?;
+    }
+    omit_old_versions(&mut components, &options.since_version);
+    adopt_bom_licenses(&mut config, &mut components, &options.checks);
+
+    for (name, dep) in components.iter_mut() {
+        let target_versions = found_in.remove(name).unwrap_or_default();
+        if dir_options.show_targets {
+            dep.targets = Some(if target_versions.len() == dirs.len() {
+                Targets::All
+            } else {
+                Targets::Only(target_versions.keys().cloned().collect())
+            });
+        }
+        if dir_options.show_target_versions {
+            dep.target_versions = Some(target_versions);
+        }
+    }
 
-    gen_licenses_for(&components, &config, w)?;
+    gen_licenses_for(&components, &config, None, options, w)?;
 
     Ok(())
 }
 
 /// Generate a license summary file from a build log and configuration file
+///
+/// `subject` names the product in the opening sentence of the report
 pub(crate) fn gen_licenses_for<W>(
-    components: &BTreeMap<String, Vec<Version>>,
+    components: &BTreeMap<String, Dependency>,
     config: &Config,
-    mut w: W,
+    subject: Option<&str>,
+    options: &ReportOptions,
+    w: W,
 ) -> Result<(), anyhow::Error>
 where
     W: std::io::Write,
 {
-    // first summarize the licenses
-    let mut licenses: BTreeMap<&'static str, LicenseInfo> = BTreeMap::new();
-    for (name, _) in components.iter() {
-        let pkg = config.third_party.get(name).ok_or_else(|| {
-            anyhow::Error::msg(format!("3rd party package {name} not in the allow list"))
-        })?;
-        for license in pkg.licenses.iter() {
-            licenses.insert(license.spdx_short(), license.info());
+    let encoding = options.output_encoding;
+    let mut w = EncodedWriter::new(w, encoding);
+    let mut report = Report::new(components, config, options)?;
+    report.subject = subject;
+    if let Some(path) = &options.attributions {
+        report.write_attributions(&mut EncodedWriter::new(
+            std::fs::File::create(path)?,
+            encoding,
+        ))?;
+        report.omit_attributions();
+    }
+    let location = match (&options.text_output, &options.license_dir) {
+        (None, None) => "at the end of this document",
+        (Some(_), None) => "in a separate document",
+        (None, Some(_)) => "in the files listed at the end of this document",
+        (Some(_), Some(_)) => "in the files listed in a separate document",
+    };
+
+    match (&options.summary_output, &options.text_output) {
+        (Some(summary_path), Some(text_path)) => {
+            report.write_summary(
+                &mut EncodedWriter::new(std::fs::File::create(summary_path)?, encoding),
+                options.format,
+                options.wrap,
+                location,
+            )?;
+            report.write_texts(
+                &mut EncodedWriter::new(std::fs::File::create(text_path)?, encoding),
+                options.format,
+                options.license_dir.as_deref(),
+            )?;
+        }
+        _ => {
+            report.write_summary(&mut w, options.format, options.wrap, location)?;
+            report.write_texts(&mut w, options.format, options.license_dir.as_deref())?;
         }
     }
+    // a buffered file would otherwise be flushed on drop, ignoring any error
+    std::io::Write::flush(&mut w)?;
 
-    writeln!(
-        w,
-        "This distribution contains open source dependencies under the following licenses:"
-    )?;
-    writeln!(w)?;
-    for (spdx, info) in licenses.iter() {
-        writeln!(w, "  * {}", spdx)?;
-        writeln!(w, "      - {}", info.url)?;
+    if let Some(path) = &options.csv {
+        report.write_csv(&mut std::fs::File::create(path)?)?;
     }
-    writeln!(w)?;
-    writeln!(w, "Copies of these licenses are provided at the end of this document. They may also be obtained from the URLs above.")?;
-    writeln!(w)?;
 
-    for (name, versions) in components.iter() {
-        let versions: Vec<String> = versions.iter().map(|x| x.to_string()).collect();
+    #[cfg(feature = "network")]
+    if options.check_urls {
+        report.check_urls();
+    }
 
-        let pkg = config.third_party.get(name).ok_or_else(|| {
-            anyhow::Error::msg(format!("3rd party package {name} not in the allow list"))
-        })?;
-        writeln!(w, "crate: {}", pkg.id)?;
-        writeln!(w, "version(s): {}", versions.join(", "))?;
-        writeln!(w, "url: {}", pkg.url())?;
+    Ok(())
+}
 
-        if pkg.licenses.is_empty() {
-            return Err(anyhow::Error::msg(format!(
-                "No license specified for {name}",
-            )));
-        }
+/// Machine-readable form of a license report
+#[derive(Serialize)]
+struct JsonReport<'a> {
+    crates: Vec<JsonCrate<'a>>,
+    licenses: Vec<JsonLicense<'a>>,
+}
 
-        let licenses: Vec<String> = pkg
+/// A distributed crate in the JSON report
+#[derive(Serialize)]
+struct JsonCrate<'a> {
+    #[serde(rename = "crate")]
+    name: &'a str,
+    versions: &'a [String],
+    url: &'a str,
+    /// license expression of the crate
+    licenses: &'a str,
+    copyrights: &'a [String],
+}
+
+/// A license used by at least one crate of the JSON report
+#[derive(Serialize)]
+struct JsonLicense<'a> {
+    spdx: &'a str,
+    url: &'a str,
+}
+
+/// Write the crates and licenses of a report as a JSON document
+pub(crate) fn write_json<W>(report: &Report, w: &mut W) -> std::io::Result<()>
+where
+    W: std::io::Write,
+{
+    let json = JsonReport {
+        crates: report
+            .crates
+            .iter()
+            .map(|entry| JsonCrate {
+                name: entry.id,
+                versions: &entry.versions,
+                url: &entry.url,
+                licenses: &entry.expression,
+                copyrights: &entry.copyright,
+            })
+            .collect(),
+        licenses: report
             .licenses
             .iter()
-            .map(|x| x.spdx_short().to_string())
+            .map(|(spdx, info)| JsonLicense {
+                spdx,
+                url: info.url,
+            })
+            .collect(),
+    };
+    serde_json::to_writer_pretty(&mut *w, &json)?;
+    writeln!(w)
+}
+
+/// Read the BOM of every directory, in the order of the directories
+///
+/// Parsing dominates the run time for large monorepos, so the BOMs are read by as many
+/// threads as there are CPUs. The first error in the order of the directories is returned.
+fn read_boms(
+    dirs: &[PathBuf],
+    bom_file: &str,
+    bom_options: &BomOptions,
+) -> Result<Vec<Bom>, anyhow::Error> {
+    let workers = std::thread::available_parallelism().map_or(1, |x| x.get());
+    let chunk_size = dirs.len().div_ceil(workers).max(1);
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = dirs
+            .chunks(chunk_size)
+            .map(|chunk| {
+                scope.spawn(move || {
+                    chunk
+                        .iter()
+                        .map(|dir| read_bom(&dir.join(bom_file), bom_options))
+                        .collect::<Vec<_>>()
+                })
+            })
             .collect();
-        writeln!(w, "license(s): {}", licenses.join(" AND "))?;
 
-        // write out copyright statements
-        for lic in pkg.licenses.iter() {
-            if let Some(lines) = lic.copyright() {
-                for line in lines {
-                    writeln!(w, "{}", line)?;
+        let mut boms = Vec::with_capacity(dirs.len());
+        for handle in handles {
+            for bom in handle.join().expect("a thread reading BOMs panicked") {
+                boms.push(bom?);
+            }
+        }
+        Ok(boms)
+    })
+}
+
+/// Remove the versions that are also distributed according to the baseline BOM,
+/// dropping the crates that have no other version
+fn subtract_baseline(
+    components: &mut BTreeMap<String, Dependency>,
+    baseline_path: &Path,
+    config: &Config,
+    bom_options: &BomOptions,
+) -> Result<(), anyhow::Error> {
+    let baseline = extract_deps(read_bom(baseline_path, bom_options)?, config, bom_options)?;
+    components.retain(|name, dep| {
+        if let Some(present) = baseline.get(name) {
+            dep.versions.retain(|x| {
+                let keep = !present.versions.contains(x);
+                if !keep {
+                    warnings::debug(format!("skipping {name} {x}: present in the baseline"));
+                }
+                keep
+            });
+        }
+        !dep.versions.is_empty()
+    });
+    Ok(())
+}
+
+/// Describe every crate whose versions are not semver compatible with each other,
+/// or that has more than `max_versions` versions
+fn version_skew(
+    components: &BTreeMap<String, Dependency>,
+    max_versions: Option<usize>,
+) -> Vec<String> {
+    let mut skews = Vec::new();
+    for (name, dep) in components.iter() {
+        // the left-most non-zero component decides compatibility, as in Cargo
+        let series: BTreeSet<(u64, u64, u64)> = dep
+            .versions
+            .iter()
+            .map(|x| match (x.major, x.minor) {
+                (0, 0) => (0, 0, x.patch),
+                (0, minor) => (0, minor, 0),
+                (major, _) => (major, 0, 0),
+            })
+            .collect();
+        let versions: Vec<String> = dep.versions.iter().map(|x| x.to_string()).collect();
+        if series.len() > 1 {
+            skews.push(format!(
+                "{name} is distributed in {} incompatible series: {}",
+                series.len(),
+                versions.join(", ")
+            ));
+        } else if let Some(max) = max_versions.filter(|max| versions.len() > *max) {
+            skews.push(format!(
+                "{name} is distributed in {} versions, more than {max}: {}",
+                versions.len(),
+                versions.join(", ")
+            ));
+        }
+    }
+    skews
+}
+
+/// Remove the versions that are not newer than the one given for their crate,
+/// dropping the crates that have no other version
+fn omit_old_versions(components: &mut BTreeMap<String, Dependency>, since: &[(String, Version)]) {
+    for (name, since) in since {
+        let Some(dep) = components.get_mut(name) else {
+            warnings::warn(format!(
+                "--since-version names {name}, which is not distributed"
+            ));
+            continue;
+        };
+        dep.versions.retain(|x| {
+            let keep = x > since;
+            if !keep {
+                warnings::note(format!("omitting {name} {x}: not newer than {since}"));
+            }
+            keep
+        });
+        if dep.versions.is_empty() {
+            components.remove(name);
+        }
+    }
+}
+
+/// Read a CycloneDX BOM in JSON format, specification 1.3 to 1.6, or in XML format, specification 1.3 or 1.4
+///
+/// A path of `-` reads the BOM from stdin
+pub(crate) fn read_bom(path: &Path, bom_options: &BomOptions) -> Result<Bom, anyhow::Error> {
+    let content = read_bom_content(path)?;
+    parse_bom(path, &content, bom_options)
+}
+
+/// Read the raw content of a BOM, from stdin if the path is `-`
+pub(crate) fn read_bom_content(path: &Path) -> Result<Vec<u8>, anyhow::Error> {
+    read_content(path, std::io::stdin().lock())
+}
+
+/// Read the raw content of a BOM, from the `stdin` reader if the path is `-`
+fn read_content<R>(path: &Path, mut stdin: R) -> Result<Vec<u8>, anyhow::Error>
+where
+    R: std::io::Read,
+{
+    warnings::info(format!("reading BOM {}", path.display()));
+    if path == Path::new("-") {
+        let mut content = Vec::new();
+        stdin.read_to_end(&mut content)?;
+        Ok(content)
+    } else {
+        Ok(std::fs::read(path)?)
+    }
+}
+
+/// Parse the content of a BOM read from `path`, in any of the formats accepted by [`read_bom`]
+pub(crate) fn parse_bom(
+    path: &Path,
+    content: &[u8],
+    bom_options: &BomOptions,
+) -> Result<Bom, anyhow::Error> {
+    match content.iter().find(|x| !x.is_ascii_whitespace()) {
+        Some(b'{') => read_json_bom(path, content, bom_options),
+        Some(b'<') => read_xml_bom(path, content),
+        _ => Err(anyhow::Error::msg(format!(
+            "{} is neither a JSON nor an XML document",
+            path.display()
+        ))),
+    }
+}
+
+/// CycloneDX specification version declared by the content of a BOM, in JSON or XML format
+pub(crate) fn spec_version(content: &[u8]) -> Option<String> {
+    if let Ok(json) = serde_json::from_slice::<serde_json::Value>(content) {
+        return json
+            .get("specVersion")
+            .and_then(|x| x.as_str())
+            .map(|x| x.to_string());
+    }
+    // the namespace of an XML BOM ends with the version, e.g. http://cyclonedx.org/schema/bom/1.4
+    let text = String::from_utf8_lossy(content);
+    let (_, rest) = text.split_once("cyclonedx.org/schema/bom/")?;
+    let version: String = rest
+        .chars()
+        .take_while(|x| x.is_ascii_digit() || *x == '.')
+        .collect();
+    (!version.is_empty()).then_some(version)
+}
+
+/// Read a CycloneDX BOM in XML format, whose namespace identifies the specification
+///
+/// Unlike JSON BOMs, the components must have a name and a version
+fn read_xml_bom(path: &Path, content: &[u8]) -> Result<Bom, anyhow::Error> {
+    Bom::parse_from_xml_v1_4(content)
+        .or_else(|_| Bom::parse_from_xml_v1_3(content))
+        .map_err(|err| {
+            anyhow::Error::msg(format!(
+                "{} is not a CycloneDX 1.3 or 1.4 XML BOM: {err}",
+                path.display()
+            ))
+        })
+}
+
+/// Read a CycloneDX BOM in JSON format
+fn read_json_bom(
+    path: &Path,
+    content: &[u8],
+    bom_options: &BomOptions,
+) -> Result<Bom, anyhow::Error> {
+    let mut json: serde_json::Value = serde_json::from_slice(content).map_err(|err| {
+        anyhow::Error::msg(format!("{} is not valid JSON: {err}", path.display()))
+    })?;
+    let spec_version = json
+        .get("specVersion")
+        .and_then(|x| x.as_str())
+        .unwrap_or_default()
+        .to_string();
+    match spec_version.as_str() {
+        "1.3" | "1.4" => {}
+        // cyclonedx-bom has no model for these versions, but the fields read from components
+        // are unchanged and the 1.4 model ignores the fields it does not know
+        "1.5" | "1.6" => {
+            warnings::debug(format!(
+                "reading CycloneDX {spec_version} BOM {} as 1.4",
+                path.display()
+            ));
+            json["specVersion"] = serde_json::Value::from("1.4");
+            downgrade_tools(&mut json);
+        }
+        "" => {
+            return Err(anyhow::Error::msg(format!(
+                "{} does not declare a CycloneDX specVersion",
+                path.display()
+            )))
+        }
+        other => {
+            return Err(anyhow::Error::msg(format!(
+                "{} uses CycloneDX specVersion {other}, only 1.3 to 1.6 are supported",
+                path.display()
+            )))
+        }
+    }
+    if let Some(components) = json.get_mut("components").and_then(|x| x.as_array_mut()) {
+        for component in components.iter_mut() {
+            fill_from_purl(component, bom_options.frozen)?;
+        }
+    }
+    Ok(Bom::parse_from_json(serde_json::to_vec(&json)?.as_slice())?)
+}
+
+/// Replace the `metadata.tools` object introduced by CycloneDX 1.5 with the list of tools of 1.4
+///
+/// The tools given as components become 1.4 tools, the ones given as services are dropped
+fn downgrade_tools(json: &mut serde_json::Value) {
+    let Some(tools) = json.pointer_mut("/metadata/tools") else {
+        return;
+    };
+    let Some(components) = tools.as_object().map(|x| x.get("components")) else {
+        return;
+    };
+    let legacy: Vec<serde_json::Value> = components
+        .and_then(|x| x.as_array())
+        .into_iter()
+        .flatten()
+        .map(|component| {
+            let mut tool = serde_json::Map::new();
+            let vendor = ["publisher", "group", "author"]
+                .iter()
+                .find_map(|x| component.get(*x).filter(|x| x.is_string()));
+            if let Some(vendor) = vendor {
+                tool.insert("vendor".to_string(), vendor.clone());
+            }
+            for field in ["name", "version"] {
+                if let Some(value) = component.get(field).filter(|x| x.is_string()) {
+                    tool.insert(field.to_string(), value.clone());
                 }
             }
+            serde_json::Value::Object(tool)
+        })
+        .collect();
+    *tools = serde_json::Value::Array(legacy);
+}
+
+/// Derive the name and/or version of a component from its purl when the BOM omits them
+///
+/// If `frozen`, a missing name or version is an error instead
+fn fill_from_purl(component: &mut serde_json::Value, frozen: bool) -> Result<(), anyhow::Error> {
+    let is_missing = |key: &str| {
+        component
+            .get(key)
+            .and_then(|x| x.as_str())
+            .is_none_or(|x| x.is_empty())
+    };
+    let missing_name = is_missing("name");
+    let missing_version = is_missing("version");
+    if !missing_name && !missing_version {
+        return Ok(());
+    }
+
+    let purl = match component.get("purl").and_then(|x| x.as_str()) {
+        Some(x) => x.to_string(),
+        None => return Ok(()),
+    };
+    if frozen {
+        return Err(anyhow::Error::msg(format!(
+            "Component {purl} has no name or version and --frozen forbids inferring them from its purl"
+        )));
+    }
+    let parsed = PackageUrl::from_str(&purl)
+        .map_err(|err| anyhow::Error::msg(format!("Invalid purl {purl}: {err}")))?;
+    if parsed.ty() != "cargo" {
+        return Err(anyhow::Error::msg(format!(
+            "Component identified only by purl {purl} is not a cargo package"
+        )));
+    }
+
+    if let Some(object) = component.as_object_mut() {
+        if missing_name {
+            object.insert("name".to_string(), parsed.name().into());
+        }
+        if missing_version {
+            if let Some(version) = parsed.version() {
+                object.insert("version".to_string(), version.into());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Use the licenses declared in the BOM for crates that are not in the configuration, if allowed
+pub(crate) fn adopt_bom_licenses(
+    config: &mut Config,
+    components: &mut BTreeMap<String, Dependency>,
+    checks: &CheckOptions,
+) {
+    if !checks.allow_bom_licenses {
+        return;
+    }
+
+    for (name, dep) in components.iter_mut() {
+        if config.third_party.contains_key(name) {
+            continue;
         }
+        if let Some(pkg) = dep.declared.take() {
+            warnings::warn(format!(
+                "{name} is not in the allow list, using the license {} declared in the BOM",
+                pkg.expression()
+            ));
+            config.third_party.insert(name.clone(), pkg);
+        }
+    }
+    config.apply_default_or_choice();
+}
 
-        writeln!(w)?;
+/// License expression declared by a component of the BOM, if every license is identified by SPDX id or expression
+pub(crate) fn declared_expression(component: &Component) -> Option<Expression> {
+    let choices = &component.licenses.as_ref()?.0;
+
+    let mut items = Vec::new();
+    for choice in choices.iter() {
+        match choice {
+            LicenseChoice::License(license) => match &license.license_identifier {
+                LicenseIdentifier::SpdxId(id) => items.push(Expression::License(id.to_string())),
+                LicenseIdentifier::Name(_) => return None,
+            },
+            LicenseChoice::Expression(expression) => {
+                items.push(Expression::parse(&expression.to_string()).ok()?)
+            }
+        }
+    }
+    if items.is_empty() {
+        return None;
+    }
+    // every entry in the list of licenses applies
+    Some(Expression::combine(Combine::And, items))
+}
+
+/// Build a configuration entry from the licenses a component declares in the BOM
+///
+/// Returns `None` unless every license is recognized and the expression uses a single operator
+fn declared_package(name: &str, component: &Component) -> Option<Package> {
+    let expression = declared_expression(component)?;
+
+    let copyright = || match &component.copyright {
+        Some(x) => Copyright::Lines(vec![x.to_string()]),
+        None => Copyright::NotPresent,
+    };
+    let licenses = expression
+        .ids()
+        .into_iter()
+        .map(|id| License::from_spdx(id, copyright()))
+        .collect::<Option<Vec<License>>>()?;
+    if licenses.is_empty() {
+        return None;
+    }
+
+    // groups that mix AND and OR can only be described with an expression
+    let (combine, expression) = match &expression {
+        Expression::Compound(combine, _) if expression.is_flat() => (*combine, None),
+        Expression::License(_) => (Combine::And, None),
+        Expression::Compound(..) => (Combine::And, Some(expression.to_string())),
+    };
+
+    Some(Package {
+        id: name.to_string(),
+        source: git_url(component)
+            .map(|x| Source::from_vcs_url(&x))
+            .unwrap_or(Source::CratesIo),
+        licenses,
+        combine,
+        expression,
+        comment: None,
+        chosen: None,
+        chosen_rationale: None,
+    })
+}
+
+/// The git repository a component was built from, according to the `vcs_url` qualifier of its purl
+pub(crate) fn git_url(component: &Component) -> Option<String> {
+    let purl = component.purl.as_ref()?.to_string();
+    let purl = PackageUrl::from_str(&purl).ok()?;
+    let url = purl.qualifiers().get("vcs_url")?;
+    url.starts_with("git+").then(|| url.to_string())
+}
+
+/// Describe why the purl of a component is missing, malformed or disagrees with the component, if it does
+fn purl_problem(component: &Component) -> Option<String> {
+    let name = component.name.to_string();
+    let purl = match &component.purl {
+        Some(x) => x.to_string(),
+        None => return Some(format!("{name} has no purl")),
+    };
+    let parsed = match PackageUrl::from_str(&purl) {
+        Ok(x) => x,
+        Err(err) => return Some(format!("{name} has an invalid purl {purl}: {err}")),
+    };
+    if parsed.ty() != "cargo" {
+        return Some(format!(
+            "{name} has a purl {purl} that is not a cargo package"
+        ));
+    }
+    if parsed.name() != name {
+        return Some(format!("{name} has a purl {purl} with a different name"));
+    }
+    // build metadata is not significant, as for the versions in the report
+    let parse = |x: &str| {
+        semver::Version::parse(x).ok().map(|mut x| {
+            x.build = semver::BuildMetadata::EMPTY;
+            x
+        })
+    };
+    let version = component.version.as_ref().map(|x| x.to_string());
+    let agrees = match (version.as_deref(), parsed.version()) {
+        (Some(a), Some(b)) => a == b || parse(a).is_some_and(|a| parse(b) == Some(a)),
+        _ => false,
+    };
+    (!agrees).then(|| format!("{name} has a purl {purl} with a different or missing version"))
+}
+
+/// Describe why the URL printed for a package would not be usable, if it would not be
+fn url_problem(pkg: &Package) -> Option<&'static str> {
+    let url = pkg.url();
+    let rest = match url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))
+    {
+        Some(x) => x,
+        None => return Some("is not an http(s) URL"),
+    };
+    if rest.split('/').next().is_none_or(str::is_empty) {
+        return Some("has no host");
+    }
+    if url.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return Some("contains whitespace");
+    }
+
+    match pkg.source {
+        Source::CratesIo => {
+            let valid = !pkg.id.is_empty()
+                && pkg
+                    .id
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+            (!valid).then_some("does not name a valid crate")
+        }
+        Source::Git { .. } => None,
+    }
+}
+
+/// Collect every problem that prevents a report from being generated for the components
+pub(crate) fn check_components(
+    components: &BTreeMap<String, Dependency>,
+    config: &Config,
+    checks: &CheckOptions,
+) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    for (name, dep) in components.iter() {
+        let pkg = match config.third_party.get(name) {
+            Some(x) => x,
+            None => {
+                problems.push(format!("3rd party package {name} not in the allow list"));
+                continue;
+            }
+        };
+
+        if pkg.licenses.is_empty() {
+            problems.push(format!("No license specified for {name}"));
+        }
+
+        if pkg.licenses.iter().any(|x| matches!(x, License::Unknown)) {
+            problems.push(format!("Unknown license specified for {name}"));
+            continue;
+        }
+
+        // licenses that are only alternatives of an OR are not used
+        for id in pkg.expression().first_alternative() {
+            if config.denied_licenses.contains(id)
+                || config
+                    .denied_licenses
+                    .contains(compat::without_exception(id))
+            {
+                problems.push(format!(
+                    "{name} is distributed under {id}, which is a denied license"
+                ));
+            }
+        }
+
+        match compat::at_or_above(pkg, checks.min_severity) {
+            Some(compat::Flagged::Category(category)) => problems.push(format!(
+                "{name} has a {} license, which is not allowed by --min-severity",
+                category.name()
+            )),
+            Some(compat::Flagged::Unknown(ids)) => problems.push(format!(
+                "{name} has a license of unknown severity ({}), which --min-severity cannot allow",
+                ids.join(", ")
+            )),
+            None => {}
+        }
+
+        if checks.deny_pre_release {
+            for version in dep.versions.iter().filter(|x| !x.pre.is_empty()) {
+                problems.push(format!(
+                    "Pre-release version {version} of {name} is not allowed"
+                ));
+            }
+        }
+
+        if checks.deny_git_deps {
+            for url in dep.git_urls.iter() {
+                problems.push(format!(
+                    "{name} is a git dependency ({url}), which is not allowed"
+                ));
+            }
+        }
+
+        if checks.require_urls {
+            if let Some(problem) = url_problem(pkg) {
+                problems.push(format!("The URL of {name} ({}) {problem}", pkg.url()));
+            }
+        }
+
+        if checks.pedantic {
+            for license in pkg
+                .effective_licenses()
+                .filter(|x| x.is_copyright_missing())
+            {
+                problems.push(format!(
+                    "No copyright statement was provided for the {} license of {name}",
+                    license.spdx_short()
+                ));
+            }
+        }
     }
 
-    for info in licenses.values() {
-        writeln!(w, "{}", info.text)?;
-        writeln!(w)?;
+    problems
+}
+
+/// Check that a report could be generated for a BOM, without writing the report
+pub(crate) fn check_bom<W>(
+    bom_path: &Path,
+    config_options: &ConfigOptions,
+    bom_options: &BomOptions,
+    checks: &CheckOptions,
+    mut w: W,
+) -> Result<(), anyhow::Error>
+where
+    W: std::io::Write,
+{
+    let mut config = config_options.load()?;
+    let mut components = extract_deps(read_bom(bom_path, bom_options)?, &config, bom_options)?;
+    adopt_bom_licenses(&mut config, &mut components, checks);
+
+    let problems = check_components(&components, &config, checks);
+    if !problems.is_empty() {
+        return Err(anyhow::Error::msg(format!(
+            "{} problem(s) found:\n{}",
+            problems.len(),
+            problems.join("\n")
+        )));
     }
 
+    writeln!(
+        w,
+        "{} crate(s) checked, no problems found",
+        components.len()
+    )?;
+
     Ok(())
 }
 
-fn extract_deps(
+/// Extract the distributed dependencies from a BOM, skipping build-only and vendor packages
+pub(crate) fn extract_deps(
     bom: Bom,
     config: &Config,
-) -> Result<BTreeMap<String, Vec<Version>>, anyhow::Error> {
+    options: &BomOptions,
+) -> Result<BTreeMap<String, Dependency>, anyhow::Error> {
     let mut deps = BTreeMap::new();
 
     let components = &bom
@@ -152,28 +976,956 @@ fn extract_deps(
         .ok_or_else(|| anyhow::Error::msg("required field 'components' is 'None'"))?
         .0;
 
+    if options.validate_purls {
+        let problems: Vec<String> = components.iter().filter_map(purl_problem).collect();
+        if !problems.is_empty() {
+            return Err(anyhow::Error::msg(format!(
+                "{} invalid purl(s):\n{}",
+                problems.len(),
+                problems.join("\n")
+            )));
+        }
+    }
+
+    for name in dual_role_crates(components, config, options) {
+        warnings::warn(format!(
+            "{name} is listed in build_only but is also a runtime dependency according to the BOM"
+        ));
+    }
+
     'deps: for component in components.iter() {
         let version = component.version.as_ref().ok_or_else(|| {
             anyhow::Error::msg(format!("Missing version in component {}", component.name))
         })?;
-        let version = semver::Version::parse(version)?;
-        if config.build_only.contains(component.name.as_ref()) {
+        let mut version = semver::Version::parse(version)?;
+        version.build = semver::BuildMetadata::EMPTY;
+
+        let mut name = component.name.to_string();
+        if options.frozen && !config.contains(&name) {
+            if let Some(key) = config.find_ignore_case(&name) {
+                return Err(anyhow::Error::msg(format!(
+                    "{name} only matches {key} in the configuration when ignoring case, which --frozen forbids"
+                )));
+            }
+        }
+        if options.ignore_case && !config.contains(&name) {
+            if let Some(key) = config.find_ignore_case(&name) {
+                warnings::warn(format!(
+                    "{name} only matches {key} in the configuration when ignoring case"
+                ));
+                name = key.to_string();
+            }
+        }
+
+        if config.build_only.contains(&name) {
+            warnings::debug(format!("skipping {name} {version}: listed in build_only"));
             continue 'deps;
         }
 
-        if config.vendor.contains_key(component.name.as_ref()) {
+        if config.vendor.contains_key(&name) {
+            warnings::debug(format!("skipping {name} {version}: listed in vendor"));
             continue 'deps;
         }
 
-        match deps.entry(component.name.to_string()) {
-            Entry::Vacant(x) => {
-                x.insert(vec![version]);
-            }
-            Entry::Occupied(x) => {
-                x.into_mut().push(version);
+        let author = component
+            .author
+            .as_ref()
+            .or(component.publisher.as_ref())
+            .map(|x| x.to_string());
+
+        if options.frozen && config.copyright_placeholder.is_none() {
+            let missing = config
+                .third_party
+                .get(&name)
+                .is_some_and(|pkg| pkg.effective_licenses().any(|x| x.is_copyright_missing()));
+            if missing {
+                return Err(anyhow::Error::msg(format!(
+                    "{name} has no copyright statement and --frozen forbids the default placeholder, set copyright_placeholder in the configuration"
+                )));
             }
         }
+
+        let declared = if config.contains(&name) {
+            None
+        } else {
+            declared_package(&name, component)
+        };
+
+        let dep = deps.entry(name).or_insert_with(|| Dependency {
+            versions: Vec::new(),
+            authors: BTreeSet::new(),
+            git_urls: BTreeSet::new(),
+            declared: None,
+            targets: None,
+            target_versions: None,
+        });
+        dep.git_urls.extend(git_url(component));
+        if dep.declared.is_none() {
+            dep.declared = declared;
+        }
+        if !dep.versions.contains(&version) {
+            dep.versions.push(version);
+        }
+        dep.authors.extend(author);
     }
 
+    warnings::info(format!(
+        "{} distributed crate(s) found in the BOM",
+        deps.len()
+    ));
     Ok(deps)
 }
+
+/// Crates listed in build_only that the BOM also reaches through a runtime edge,
+/// only looked for with --include-transitive-build-deps
+///
+/// cargo-cyclonedx only scopes a crate as excluded if no runtime edge leads to it
+fn dual_role_crates(
+    components: &[Component],
+    config: &Config,
+    options: &BomOptions,
+) -> BTreeSet<String> {
+    if !options.include_transitive_build_deps {
+        return BTreeSet::new();
+    }
+    components
+        .iter()
+        .filter(|x| !matches!(x.scope, Some(Scope::Excluded)))
+        .map(|x| {
+            let name = x.name.as_ref();
+            match config.find_ignore_case(name) {
+                Some(key) if options.ignore_case && !config.contains(name) => key,
+                _ => name,
+            }
+        })
+        .filter(|name| config.build_only.contains(*name))
+        .map(|name| name.to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{
+        args, bom, bom_args, bom_with, component, config_options, gen_report, TempDir, CONFIG,
+    };
+
+    fn config(json: &str) -> Config {
+        let config: Config = serde_json::from_str(json).unwrap();
+        config.validate().unwrap();
+        config
+    }
+
+    fn gen_in_dirs(dir: &TempDir, dir_args: &[&str]) -> Result<String, anyhow::Error> {
+        let config = dir.write("config.json", CONFIG);
+        let mut out = Vec::new();
+        gen_licenses_in_dirs(
+            &dir.path().join("targets"),
+            "bom.json",
+            &config_options(&[&config]),
+            &args(dir_args),
+            &BomOptions::default(),
+            &args(&[]),
+            &mut out,
+        )?;
+        Ok(String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn require_all_lists_every_directory_without_a_bom() {
+        let dir = TempDir::new("require-all");
+        dir.write("targets/a/bom.json", &bom(&[("serde", "1.0.188")]));
+        std::fs::create_dir_all(dir.path().join("targets/b")).unwrap();
+        std::fs::create_dir_all(dir.path().join("targets/c")).unwrap();
+
+        let err = gen_in_dirs(&dir, &["--require-all"]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "bom.json is missing from the following directories: {}, {}",
+                dir.path().join("targets/b").display(),
+                dir.path().join("targets/c").display()
+            )
+        );
+    }
+
+    #[test]
+    fn since_version_only_reports_newer_versions() {
+        let bom = bom(&[
+            ("serde", "1.0.100"),
+            ("serde", "1.0.188"),
+            ("serde", "1.0.190"),
+            ("libc", "0.2.150"),
+        ]);
+
+        let report = gen_report(
+            CONFIG,
+            &bom,
+            &[
+                "--since-version",
+                "serde@1.0.188",
+                "--since-version",
+                "libc@0.2.150",
+            ],
+        )
+        .unwrap();
+        assert!(report.contains("crate: serde\nversion(s): 1.0.190\n"));
+        assert!(!report.contains("crate: libc"));
+    }
+
+    #[test]
+    fn denied_licenses_fail_naming_the_crate_and_license() {
+        let config = CONFIG.replacen('{', r#"{"denied_licenses":["ISC","GPL-3.0-only"],"#, 1);
+        let bom = bom(&[("serde", "1.0.188"), ("ring", "0.17.5")]);
+
+        assert!(gen_report(CONFIG, &bom, &[]).is_ok());
+        let err = gen_report(&config, &bom, &[]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "ring is distributed under ISC, which is a denied license"
+        );
+    }
+
+    #[test]
+    fn json_format_lists_crates_and_licenses_by_field_name() {
+        let bom = bom(&[
+            ("serde", "1.0.188"),
+            ("ring", "0.17.5"),
+            ("libc", "0.2.150"),
+        ]);
+
+        let report = gen_report(CONFIG, &bom, &["--format", "json"]).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&report).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "crates": [
+                    {
+                        "crate": "libc",
+                        "versions": ["0.2.150"],
+                        "url": "https://crates.io/crates/libc",
+                        "licenses": "MIT",
+                        "copyrights": [
+                            "No copyright statement was provided by the author even though the license may refer to it"
+                        ],
+                    },
+                    {
+                        "crate": "ring",
+                        "versions": ["0.17.5"],
+                        "url": "https://crates.io/crates/ring",
+                        "licenses": "OpenSSL AND ISC",
+                        "copyrights": ["Copyright 2015-2016 Brian Smith."],
+                    },
+                    {
+                        "crate": "serde",
+                        "versions": ["1.0.188"],
+                        "url": "https://crates.io/crates/serde",
+                        "licenses": "MIT",
+                        "copyrights": ["Copyright (c) 2014 The Rust Project Developers"],
+                    },
+                ],
+                "licenses": [
+                    {"spdx": "ISC", "url": "https://spdx.org/licenses/ISC.html"},
+                    {"spdx": "MIT", "url": "https://spdx.org/licenses/MIT.html"},
+                    {"spdx": "OpenSSL", "url": "https://spdx.org/licenses/OpenSSL.html"},
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn deny_pre_release_fails_on_pre_release_versions() {
+        let bom = bom(&[("serde", "1.0.0-beta.1"), ("libc", "0.2.150")]);
+
+        assert!(gen_report(CONFIG, &bom, &[]).is_ok());
+        let err = gen_report(CONFIG, &bom, &["--deny-pre-release"]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Pre-release version 1.0.0-beta.1 of serde is not allowed"
+        );
+    }
+
+    #[test]
+    fn ignore_case_resolves_crates_through_the_configuration() {
+        let bom = bom(&[("serde", "1.0.188"), ("Serde", "1.0.190")]);
+
+        assert!(gen_report(CONFIG, &bom, &[]).is_err());
+        let report = gen_report(CONFIG, &bom, &["--ignore-case"]).unwrap();
+        assert!(report.contains("crate: serde\nversion(s): 1.0.188, 1.0.190\n"));
+        assert!(!report.contains("Serde"));
+    }
+
+    #[test]
+    fn pedantic_fails_on_a_missing_copyright() {
+        let bom = bom(&[("serde", "1.0.188"), ("libc", "0.2.150")]);
+
+        assert!(gen_report(CONFIG, &bom, &[]).is_ok());
+        let err = gen_report(CONFIG, &bom, &["--pedantic"]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "No copyright statement was provided for the MIT license of libc"
+        );
+    }
+
+    fn check(bom: &str) -> Result<String, anyhow::Error> {
+        let dir = TempDir::new("check-bom");
+        let config = dir.write("config.json", CONFIG);
+        let bom = dir.write("bom.json", bom);
+        let mut out = Vec::new();
+        check_bom(
+            &bom,
+            &config_options(&[&config]),
+            &BomOptions::default(),
+            &args(&[]),
+            &mut out,
+        )?;
+        Ok(String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn check_bom_fails_on_an_uncovered_crate() {
+        assert_eq!(
+            check(&bom(&[("serde", "1.0.188"), ("cc", "1.0.83")])).unwrap(),
+            "1 crate(s) checked, no problems found\n"
+        );
+
+        let err = check(&bom(&[("serde", "1.0.188"), ("tokio", "1.35.0")])).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "1 problem(s) found:\n3rd party package tokio not in the allow list"
+        );
+    }
+
+    #[test]
+    fn summary_and_texts_split_the_report() {
+        let dir = TempDir::new("split");
+        let (summary, text) = (dir.path().join("summary.txt"), dir.path().join("text.txt"));
+        let bom = bom(&[("serde", "1.0.188"), ("ring", "0.17.5")]);
+
+        let report = gen_report(CONFIG, &bom, &[]).unwrap();
+        let out = gen_report(
+            CONFIG,
+            &bom,
+            &[
+                "--summary-output",
+                summary.to_str().unwrap(),
+                "--text-output",
+                text.to_str().unwrap(),
+            ],
+        )
+        .unwrap();
+        assert_eq!(out, "");
+
+        let summary = std::fs::read_to_string(summary).unwrap();
+        let text = std::fs::read_to_string(text).unwrap();
+        assert!(summary.contains("crate: serde\n"));
+        assert!(!summary.contains("==== MIT ===="));
+        assert!(text.contains("==== MIT ===="));
+        assert!(!text.contains("crate: "));
+        assert_eq!(
+            summary + &text,
+            report.replace("at the end of this document", "in a separate document")
+        );
+    }
+
+    #[test]
+    fn components_identified_only_by_purl_are_cargo_packages() {
+        let bom = bom_with(vec![
+            serde_json::json!({"type": "library", "purl": "pkg:cargo/serde@1.0.188"}),
+            serde_json::json!({"type": "library", "name": "libc", "purl": "pkg:cargo/libc@0.2.150"}),
+        ]);
+        let report = gen_report(CONFIG, &bom, &[]).unwrap();
+        assert!(report.contains("crate: serde\nversion(s): 1.0.188\n"));
+        assert!(report.contains("crate: libc\nversion(s): 0.2.150\n"));
+
+        let bom = bom_with(vec![
+            serde_json::json!({"type": "library", "purl": "pkg:npm/left-pad@1.3.0"}),
+        ]);
+        let err = gen_report(CONFIG, &bom, &[]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Component identified only by purl pkg:npm/left-pad@1.3.0 is not a cargo package"
+        );
+    }
+
+    #[test]
+    fn allow_bom_licenses_uses_the_license_declared_in_the_bom() {
+        let mut tokio = component("tokio", "1.35.0");
+        tokio["licenses"] = serde_json::json!([{"license": {"id": "MIT"}}]);
+        let bom = bom_with(vec![tokio, component("serde", "1.0.188")]);
+
+        let err = gen_report(CONFIG, &bom, &[]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "3rd party package tokio not in the allow list"
+        );
+
+        let report = gen_report(CONFIG, &bom, &["--allow-bom-licenses"]).unwrap();
+        assert!(report.contains(
+            "crate: tokio\nversion(s): 1.35.0\nurl: https://crates.io/crates/tokio\nlicense(s): MIT\n"
+        ));
+    }
+
+    #[test]
+    fn validate_purls_fails_on_a_purl_that_disagrees_with_its_component() {
+        let mut serde = component("serde", "1.0.188");
+        serde["purl"] = "pkg:cargo/serde_json@1.0.188".into();
+        let mut libc = component("libc", "0.2.150");
+        libc["purl"] = "pkg:cargo/libc@0.2.151".into();
+        let bom = bom_with(vec![serde, libc, component("ring", "0.17.5+build")]);
+
+        assert!(gen_report(CONFIG, &bom, &[]).is_ok());
+        let err = gen_report(CONFIG, &bom, &["--validate-purls"]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "2 invalid purl(s):\n\
+             serde has a purl pkg:cargo/serde_json@1.0.188 with a different name\n\
+             libc has a purl pkg:cargo/libc@0.2.151 with a different or missing version"
+        );
+    }
+
+    #[test]
+    fn deny_git_deps_fails_on_a_git_dependency() {
+        let mut serde = component("serde", "1.0.188");
+        serde["purl"] =
+            "pkg:cargo/serde@1.0.188?vcs_url=git%2Bhttps://github.com/serde-rs/serde%23abc123"
+                .into();
+        let bom = bom_with(vec![serde, component("libc", "0.2.150")]);
+
+        assert!(gen_report(CONFIG, &bom, &[]).is_ok());
+        let err = gen_report(CONFIG, &bom, &["--deny-git-deps"]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "serde is a git dependency (git+https://github.com/serde-rs/serde#abc123), which is not allowed"
+        );
+    }
+
+    #[test]
+    fn git_sourced_crates_print_their_repository_url() {
+        let config = r#"{"build_only":[],"vendor":{},"third_party":{
+            "dnp3":{"id":"dnp3","source":{"git":{"repo":"https://github.com/stepfunc/dnp3","rev":"1.5.0"}},"licenses":[{"MIT":{"copyright":"NotPresent"}}]},
+            "oo-bindgen":{"id":"oo-bindgen","source":{"git":{"repo":"https://github.com/stepfunc/oo_bindgen"}},"licenses":[{"MIT":{"copyright":"NotPresent"}}]}}}"#;
+        let bom = bom(&[("dnp3", "1.5.0"), ("oo-bindgen", "0.8.0")]);
+
+        let report = gen_report(config, &bom, &[]).unwrap();
+        assert!(report.contains("url: https://github.com/stepfunc/dnp3#1.5.0\n"));
+        assert!(report.contains("url: https://github.com/stepfunc/oo_bindgen\n"));
+        assert!(!report.contains("crates.io"));
+    }
+
+    #[test]
+    fn require_urls_fails_on_a_source_without_a_usable_url() {
+        let config = CONFIG.replace(r#""libc": {"id": "libc","#, r#""lib.c": {"id": "lib.c","#);
+        let bom = bom(&[("serde", "1.0.188"), ("lib.c", "0.2.150")]);
+
+        assert!(gen_report(&config, &bom, &[]).is_ok());
+        let err = gen_report(&config, &bom, &["--require-urls"]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "The URL of lib.c (https://crates.io/crates/lib.c) does not name a valid crate"
+        );
+    }
+
+    #[test]
+    fn require_urls_fails_on_a_git_repository_that_is_not_http() {
+        let config = CONFIG.replace(
+            r#""id": "libc", "source": "crates.io""#,
+            r#""id": "libc", "source": {"git": {"repo": "git@github.com:rust-lang/libc.git"}}"#,
+        );
+        let bom = bom(&[("serde", "1.0.188"), ("libc", "0.2.150")]);
+        let err = gen_report(&config, &bom, &["--require-urls"]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "The URL of libc (git@github.com:rust-lang/libc.git) is not an http(s) URL"
+        );
+    }
+
+    #[test]
+    fn min_severity_fails_on_an_eupl_crate_but_allows_mpl() {
+        let config = CONFIG
+            .replace(
+                r#"[{"MIT": {"copyright": "NotPresent"}}]"#,
+                r#"["EUPLv1.2"]"#,
+            )
+            .replace(r#""licenses": ["OpenSSL","#, r#""licenses": ["MPLv2","#);
+        let mpl = bom(&[("ring", "0.17.5")]);
+        let gpl = bom(&[("libc", "0.2.150")]);
+
+        assert!(gen_report(&config, &mpl, &["--min-severity", "copyleft"]).is_ok());
+        let err = gen_report(&config, &gpl, &["--min-severity", "copyleft"]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "libc has a copyleft license, which is not allowed by --min-severity"
+        );
+    }
+
+    #[test]
+    fn min_severity_does_not_call_a_custom_license_copyleft() {
+        let config = CONFIG.replace(
+            r#"[{"MIT": {"copyright": "NotPresent"}}]"#,
+            r#"[{"Custom": {"spdx": "LicenseRef-Foo", "url": "https://example.com/foo", "text": "Foo", "copyright": "NotPresent"}}]"#,
+        );
+        let bom = bom(&[("libc", "0.2.150")]);
+
+        assert!(gen_report(&config, &bom, &[]).is_ok());
+        let err = gen_report(&config, &bom, &["--min-severity", "weak-copyleft"]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "libc has a license of unknown severity (LicenseRef-Foo), which --min-severity cannot allow"
+        );
+    }
+
+    #[test]
+    fn build_metadata_is_ignored() {
+        let bom = bom(&[("serde", "1.0.188"), ("serde", "1.0.188+build.5")]);
+
+        let report = gen_report(CONFIG, &bom, &[]).unwrap();
+        assert!(report.contains("crate: serde\nversion(s): 1.0.188\n"));
+    }
+
+    #[test]
+    fn show_targets_annotates_crates_found_in_some_targets() {
+        let dir = TempDir::new("show-targets");
+        dir.write("targets/linux/bom.json", &bom(&[("serde", "1.0.188")]));
+        dir.write(
+            "targets/windows/bom.json",
+            &bom(&[("serde", "1.0.188"), ("libc", "0.2.150")]),
+        );
+
+        let report = gen_in_dirs(&dir, &[]).unwrap();
+        assert!(!report.contains("target(s)"));
+
+        let report = gen_in_dirs(&dir, &["--show-targets"]).unwrap();
+        assert!(report.contains("crate: libc\nversion(s): 0.2.150\ntarget(s): windows\n"));
+        assert!(report.contains("crate: serde\nversion(s): 1.0.188\ntarget(s): all\n"));
+    }
+
+    #[test]
+    fn show_target_versions_lists_the_versions_of_each_target() {
+        let dir = TempDir::new("target-versions");
+        dir.write("targets/linux/bom.json", &bom(&[("serde", "1.0.188")]));
+        dir.write("targets/windows/bom.json", &bom(&[("serde", "1.0.190")]));
+
+        let report = gen_in_dirs(&dir, &["--show-target-versions"]).unwrap();
+        assert!(report.contains(
+            "crate: serde\nversion(s): 1.0.188, 1.0.190\n\
+             version(s) in linux: 1.0.188\n\
+             version(s) in windows: 1.0.190\n"
+        ));
+    }
+
+    #[test]
+    fn gen_all_writes_one_notice_per_target() {
+        let dir = TempDir::new("gen-all");
+        let config = CONFIG.replacen(
+            '{',
+            r#"{"targets": {"app": {"subject": "App"}, "cli": {"subject": "CLI"}},"#,
+            1,
+        );
+        let config = dir.write("config.json", &config);
+        dir.write("targets/app/bom.json", &bom(&[("serde", "1.0.188")]));
+        dir.write("targets/cli/bom.json", &bom(&[("libc", "0.2.150")]));
+        let out = dir.path().join("out");
+        std::fs::create_dir_all(&out).unwrap();
+
+        let (options, bom_options): (ReportOptions, BomOptions) = bom_args(&[]);
+        gen_all(
+            &dir.path().join("targets"),
+            "bom.json",
+            &config_options(&[&config]),
+            &out,
+            &bom_options,
+            &options,
+        )
+        .unwrap();
+
+        let app = std::fs::read_to_string(out.join("app.txt")).unwrap();
+        assert!(app.starts_with("This distribution of App contains"));
+        assert!(app.contains("crate: serde\n"));
+        assert!(!app.contains("crate: libc\n"));
+        let cli = std::fs::read_to_string(out.join("cli.txt")).unwrap();
+        assert!(cli.starts_with("This distribution of CLI contains"));
+        assert!(cli.contains("crate: libc\n"));
+        assert!(!cli.contains("crate: serde\n"));
+    }
+
+    #[test]
+    fn gen_all_keeps_the_previous_notice_of_a_failing_target() {
+        let dir = TempDir::new("gen-all-failure");
+        let config = CONFIG.replacen(
+            '{',
+            r#"{"targets": {"app": {"subject": "App"}}, "denied_licenses": ["MIT"],"#,
+            1,
+        );
+        let config = dir.write("config.json", &config);
+        dir.write("targets/app/bom.json", &bom(&[("serde", "1.0.188")]));
+        let previous = dir.write("out/app.txt", "previous notice");
+
+        let (options, bom_options): (ReportOptions, BomOptions) = bom_args(&[]);
+        let err = gen_all(
+            &dir.path().join("targets"),
+            "bom.json",
+            &config_options(&[&config]),
+            &dir.path().join("out"),
+            &bom_options,
+            &options,
+        )
+        .unwrap_err();
+
+        assert!(err
+            .to_string()
+            .starts_with("Unable to generate the notice of app: "));
+        assert_eq!(
+            std::fs::read_to_string(&previous).unwrap(),
+            "previous notice"
+        );
+        // the temporary file is removed
+        assert_eq!(
+            std::fs::read_dir(dir.path().join("out")).unwrap().count(),
+            1
+        );
+    }
+
+    fn dual_role(scopes: &[Option<&str>], options: &[&str]) -> BTreeSet<String> {
+        let mut components: Vec<serde_json::Value> = scopes
+            .iter()
+            .map(|scope| {
+                let mut cc = component("cc", "1.0.83");
+                if let Some(scope) = scope {
+                    cc["scope"] = (*scope).into();
+                }
+                cc
+            })
+            .collect();
+        components.push(component("serde", "1.0.188"));
+        let bom = bom_with(components);
+        let (_, options): (ReportOptions, BomOptions) = bom_args(options);
+        let bom = parse_bom(Path::new("bom.json"), bom.as_bytes(), &options).unwrap();
+
+        dual_role_crates(&bom.components.unwrap().0, &config(CONFIG), &options)
+    }
+
+    #[test]
+    fn build_only_crate_with_a_runtime_edge_is_a_warning() {
+        let flag = ["--include-transitive-build-deps"];
+        // the same crate twice, once required at runtime
+        let runtime = [Some("excluded"), None];
+
+        assert_eq!(
+            dual_role(&runtime, &flag),
+            BTreeSet::from(["cc".to_string()])
+        );
+        assert!(dual_role(&runtime, &[]).is_empty());
+        assert!(dual_role(&[Some("excluded"), Some("excluded")], &flag).is_empty());
+
+        let bom = bom_with(vec![
+            component("cc", "1.0.83"),
+            component("serde", "1.0.188"),
+        ]);
+        let report = gen_report(CONFIG, &bom, &flag).unwrap();
+        assert!(!report.contains("crate: cc\n"));
+    }
+
+    #[test]
+    fn baseline_crates_are_subtracted_by_name_and_version() {
+        let dir = TempDir::new("baseline");
+        let baseline = dir.write(
+            "host.json",
+            &bom(&[("serde", "1.0.188"), ("libc", "0.2.150")]),
+        );
+        let bom = bom(&[
+            ("serde", "1.0.188"),
+            ("serde", "1.0.190"),
+            ("libc", "0.2.150"),
+            ("ring", "0.17.5"),
+        ]);
+
+        let report = gen_report(CONFIG, &bom, &["--baseline", baseline.to_str().unwrap()]).unwrap();
+        assert!(report.contains("crate: serde\nversion(s): 1.0.190\n"));
+        assert!(report.contains("crate: ring\n"));
+        assert!(!report.contains("crate: libc\n"));
+    }
+
+    #[test]
+    fn frozen_forbids_inferring_names_from_purls() {
+        let bom = bom_with(vec![
+            serde_json::json!({"type": "library", "purl": "pkg:cargo/serde@1.0.188"}),
+        ]);
+
+        assert!(gen_report(CONFIG, &bom, &[]).is_ok());
+        let err = gen_report(CONFIG, &bom, &["--frozen"]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Component pkg:cargo/serde@1.0.188 has no name or version and --frozen forbids inferring them from its purl"
+        );
+    }
+
+    #[test]
+    fn frozen_forbids_matching_names_ignoring_case() {
+        let bom = bom(&[("Serde", "1.0.188")]);
+
+        let err = gen_report(CONFIG, &bom, &["--frozen"]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Serde only matches serde in the configuration when ignoring case, which --frozen forbids"
+        );
+    }
+
+    #[test]
+    fn frozen_forbids_the_default_copyright_placeholder() {
+        let bom = bom(&[("serde", "1.0.188"), ("libc", "0.2.150")]);
+
+        let err = gen_report(CONFIG, &bom, &["--frozen"]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "libc has no copyright statement and --frozen forbids the default placeholder, set copyright_placeholder in the configuration"
+        );
+
+        let config = CONFIG.replacen('{', r#"{"copyright_placeholder": "Unknown authors","#, 1);
+        assert!(gen_report(&config, &bom, &["--frozen"]).is_ok());
+    }
+
+    #[test]
+    fn default_or_choice_selects_mit_across_several_crates() {
+        let config = r#"{"build_only":[],"vendor":{},"default_or_choice":"MIT","third_party":{
+            "either":{"id":"either","source":"crates.io","combine":"OR","licenses":[{"Apache2":{}},{"MIT":{"copyright":"NotPresent"}}]},
+            "itoa":{"id":"itoa","source":"crates.io","combine":"OR","licenses":[{"Apache2":{}},{"MIT":{"copyright":"NotPresent"}}]},
+            "ryu":{"id":"ryu","source":"crates.io","combine":"OR","chosen":"Apache-2.0","licenses":[{"Apache2":{}},{"MIT":{"copyright":"NotPresent"}}]}}}"#;
+        let bom = bom(&[("either", "1.9.0"), ("itoa", "1.0.9"), ("ryu", "1.0.15")]);
+
+        let report = gen_report(config, &bom, &[]).unwrap();
+        for name in ["either", "itoa"] {
+            assert!(report.contains(&format!(
+                "url: https://crates.io/crates/{name}\nlicense(s): MIT\n"
+            )));
+        }
+        assert!(report.contains("url: https://crates.io/crates/ryu\nlicense(s): Apache-2.0\n"));
+        assert!(!report.contains("Apache-2.0 OR MIT"));
+    }
+
+    #[test]
+    fn extracts_components_of_xml_boms() {
+        let dir = TempDir::new("xml-bom");
+        let config = config(
+            r#"{"build_only":[],"vendor":{},"third_party":{
+                "serde":{"id":"serde","source":"crates.io","licenses":[{"MIT":{"copyright":"NotPresent"}}]}}}"#,
+        );
+        for spec in ["1.3", "1.4"] {
+            let content = format!(
+                r#"
+                <?xml version="1.0" encoding="utf-8"?>
+                <bom xmlns="http://cyclonedx.org/schema/bom/{spec}" version="1">
+                  <components>
+                    <component type="library">
+                      <name>serde</name>
+                      <version>1.0.188</version>
+                      <purl>pkg:cargo/serde@1.0.188</purl>
+                    </component>
+                  </components>
+                </bom>"#
+            );
+            let path = dir.write("bom.xml", content.trim_start());
+            let bom = read_bom(&path, &BomOptions::default()).unwrap();
+
+            let deps = extract_deps(bom, &config, &BomOptions::default()).unwrap();
+            assert_eq!(deps["serde"].versions, [Version::new(1, 0, 188)]);
+        }
+
+        let path = dir.write("bom.xml", "<bom/>");
+        let err = read_bom(&path, &BomOptions::default()).unwrap_err();
+        assert!(err.to_string().starts_with(&format!(
+            "{} is not a CycloneDX 1.3 or 1.4 XML BOM: ",
+            path.display()
+        )));
+        let path = dir.write("bom.txt", "serde 1.0.188");
+        let err = read_bom(&path, &BomOptions::default()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!("{} is neither a JSON nor an XML document", path.display())
+        );
+    }
+
+    #[test]
+    fn a_path_of_dash_reads_the_bom_from_stdin() {
+        let bom = bom(&[("serde", "1.0.188")]);
+        let stdin = std::io::Cursor::new(bom.clone());
+
+        let content = read_content(Path::new("-"), stdin).unwrap();
+        assert_eq!(content, bom.as_bytes());
+        let bom = read_json_bom(Path::new("-"), &content, &BomOptions::default()).unwrap();
+        let deps = extract_deps(bom, &config(CONFIG), &BomOptions::default()).unwrap();
+        assert_eq!(deps["serde"].versions, [Version::new(1, 0, 188)]);
+
+        let dir = TempDir::new("stdin");
+        let path = dir.write("bom.json", "{}");
+        let stdin = std::io::Cursor::new("not read");
+        assert_eq!(read_content(&path, stdin).unwrap(), b"{}");
+    }
+
+    #[test]
+    fn read_boms_keeps_the_order_of_the_directories() {
+        let dir = TempDir::new("read-boms");
+        let dirs: Vec<PathBuf> = (0..12)
+            .map(|i| dir.path().join(format!("{i:02}")))
+            .collect();
+        for (i, path) in dirs.iter().enumerate() {
+            std::fs::create_dir_all(path).unwrap();
+            std::fs::write(
+                path.join("bom.json"),
+                bom(&[("serde", &format!("1.0.{i}"))]),
+            )
+            .unwrap();
+        }
+
+        let boms = read_boms(&dirs, "bom.json", &BomOptions::default()).unwrap();
+        let versions: Vec<String> = boms
+            .into_iter()
+            .map(|x| {
+                x.components.unwrap().0[0]
+                    .version
+                    .as_ref()
+                    .unwrap()
+                    .to_string()
+            })
+            .collect();
+        let expected: Vec<String> = (0..12).map(|i| format!("1.0.{i}")).collect();
+        assert_eq!(versions, expected);
+
+        // the error of the first bad BOM is returned, whichever thread reads it
+        for i in [3, 7, 11] {
+            std::fs::write(dirs[i].join("bom.json"), "{").unwrap();
+        }
+        let err = read_boms(&dirs, "bom.json", &BomOptions::default()).unwrap_err();
+        assert!(err.to_string().starts_with(&format!(
+            "{} is not valid JSON",
+            dirs[3].join("bom.json").display()
+        )));
+    }
+
+    #[test]
+    fn reads_cyclonedx_1_5_with_tools_object() {
+        let content = include_bytes!("../fixtures/bom-1.5.json");
+        let bom = parse_bom(Path::new("bom-1.5.json"), content, &BomOptions::default()).unwrap();
+
+        let tools = bom.metadata.unwrap().tools.unwrap().0;
+        assert_eq!(tools.len(), 1);
+        assert_eq!(
+            tools[0].name.as_ref().unwrap().to_string(),
+            "cargo-cyclonedx"
+        );
+        assert_eq!(tools[0].vendor.as_ref().unwrap().to_string(), "CycloneDX");
+        assert_eq!(bom.components.unwrap().0.len(), 2);
+    }
+
+    #[test]
+    fn reads_cyclonedx_1_6_and_drops_tool_services() {
+        let content = include_bytes!("../fixtures/bom-1.6.json");
+        let bom = parse_bom(Path::new("bom-1.6.json"), content, &BomOptions::default()).unwrap();
+
+        let tools = bom.metadata.unwrap().tools.unwrap().0;
+        assert_eq!(tools.len(), 1);
+        assert_eq!(tools[0].vendor.as_ref().unwrap().to_string(), "CycloneDX");
+    }
+
+    #[test]
+    fn extracts_components_of_cyclonedx_1_5() {
+        let content = include_bytes!("../fixtures/bom-1.5.json");
+        let bom = parse_bom(Path::new("bom-1.5.json"), content, &BomOptions::default()).unwrap();
+        let config = config(
+            r#"{"build_only":[],"vendor":{"libc":{"url":"https://example.com"}},"third_party":{
+                "serde":{"id":"serde","source":"crates.io","licenses":[{"MIT":{"copyright":"NotPresent"}}]}}}"#,
+        );
+
+        let deps = extract_deps(bom, &config, &BomOptions::default()).unwrap();
+        assert_eq!(deps.keys().collect::<Vec<_>>(), ["serde"]);
+        assert_eq!(deps["serde"].versions, [Version::new(1, 0, 188)]);
+    }
+
+    #[test]
+    fn rejects_unsupported_spec_versions() {
+        let content = br#"{"bomFormat":"CycloneDX","specVersion":"1.7","version":1}"#;
+        let err = parse_bom(Path::new("bom.json"), content, &BomOptions::default()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "bom.json uses CycloneDX specVersion 1.7, only 1.3 to 1.6 are supported"
+        );
+
+        let content = br#"{"bomFormat":"CycloneDX","version":1}"#;
+        let err = parse_bom(Path::new("bom.json"), content, &BomOptions::default()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "bom.json does not declare a CycloneDX specVersion"
+        );
+    }
+
+    #[test]
+    fn finds_the_spec_version_of_json_and_xml() {
+        assert_eq!(
+            spec_version(include_bytes!("../fixtures/bom-1.5.json")).as_deref(),
+            Some("1.5")
+        );
+        let xml = br#"<?xml version="1.0"?><bom xmlns="http://cyclonedx.org/schema/bom/1.4" version="1"/>"#;
+        assert_eq!(spec_version(xml).as_deref(), Some("1.4"));
+        assert_eq!(spec_version(b"not a bom"), None);
+    }
+
+    #[test]
+    fn versions_that_differ_between_directories_fail_by_default() {
+        let dir = TempDir::new("skew-default");
+        dir.write("targets/a/bom.json", &bom(&[("serde", "0.3.0")]));
+        dir.write("targets/b/bom.json", &bom(&[("serde", "2.0.0")]));
+
+        let err = gen_in_dirs(&dir, &[]).unwrap_err();
+        assert!(err.to_string().starts_with("Version mismatch in serde"));
+    }
+
+    #[test]
+    fn warn_version_skew_merges_versions_that_differ_between_directories() {
+        let dir = TempDir::new("skew-merge");
+        dir.write("targets/a/bom.json", &bom(&[("serde", "0.3.0")]));
+        dir.write("targets/b/bom.json", &bom(&[("serde", "2.0.0")]));
+
+        let report = gen_in_dirs(&dir, &["--warn-version-skew"]).unwrap();
+        assert!(report.contains("version(s): 0.3.0, 2.0.0\n"));
+    }
+
+    fn dependency(versions: &[&str]) -> Dependency {
+        Dependency {
+            versions: versions
+                .iter()
+                .map(|x| Version::parse(x).unwrap())
+                .collect(),
+            authors: BTreeSet::new(),
+            git_urls: BTreeSet::new(),
+            declared: None,
+            targets: None,
+            target_versions: None,
+        }
+    }
+
+    #[test]
+    fn version_skew_compares_semver_compatible_series() {
+        let components = BTreeMap::from([
+            ("a".to_string(), dependency(&["1.0.0", "1.9.2"])),
+            ("b".to_string(), dependency(&["0.2.1", "0.3.0"])),
+            ("c".to_string(), dependency(&["0.0.1", "0.0.2"])),
+            ("d".to_string(), dependency(&["0.4.0", "0.4.1", "0.4.2"])),
+        ]);
+
+        assert_eq!(
+            version_skew(&components, None),
+            [
+                "b is distributed in 2 incompatible series: 0.2.1, 0.3.0",
+                "c is distributed in 2 incompatible series: 0.0.1, 0.0.2",
+            ]
+        );
+        assert_eq!(
+            version_skew(&components, Some(2)),
+            [
+                "b is distributed in 2 incompatible series: 0.2.1, 0.3.0",
+                "c is distributed in 2 incompatible series: 0.0.1, 0.0.2",
+                "d is distributed in 3 versions, more than 2: 0.4.0, 0.4.1, 0.4.2",
+            ]
+        );
+    }
+}
@@ -0,0 +1,109 @@
+use crate::cli::{BomOptions, ConfigOptions};
+use crate::licenses::read_bom;
+use crate::warnings;
+use std::collections::BTreeSet;
+use std::path::Path;
+
+/// Warn about every entry of the configuration that does not match any component of the BOM
+///
+/// If `strict` is set, an error is returned if any entry is unused
+pub(crate) fn lint_config(
+    bom_path: &Path,
+    config_options: &ConfigOptions,
+    strict: bool,
+    bom_options: &BomOptions,
+) -> Result<(), anyhow::Error> {
+    let config = config_options.load()?;
+    let bom = read_bom(bom_path, bom_options)?;
+
+    let names: BTreeSet<String> = bom
+        .components
+        .iter()
+        .flat_map(|x| x.0.iter())
+        .map(|x| x.name.to_string())
+        .collect();
+    let matches = |key: &str| {
+        names.contains(key)
+            || (bom_options.ignore_case && names.iter().any(|x| x.eq_ignore_ascii_case(key)))
+    };
+
+    let buckets: [(&str, Vec<&String>); 3] = [
+        ("build_only", config.build_only.iter().collect()),
+        ("vendor", config.vendor.keys().collect()),
+        ("third_party", config.third_party.keys().collect()),
+    ];
+
+    let mut unused = 0;
+    for (bucket, keys) in buckets.iter() {
+        for key in keys.iter().filter(|x| !matches(x)) {
+            warnings::warn(format!(
+                "{key} is listed in {bucket} but does not match any component of the BOM"
+            ));
+            unused += 1;
+        }
+    }
+
+    if strict && unused > 0 {
+        return Err(anyhow::Error::msg(format!(
+            "{unused} unused entr{} in {}",
+            if unused == 1 { "y" } else { "ies" },
+            config_options.describe()
+        )));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{bom, config_options, TempDir, CONFIG};
+
+    /// Lint CONFIG against a BOM, strictly and leniently, with the path of the configuration left out of the error
+    fn lint(components: &[(&str, &str)], bom_options: &BomOptions) -> Result<(), String> {
+        let dir = TempDir::new("lint-config");
+        let config = dir.write("config.json", CONFIG);
+        let bom_path = dir.write("bom.json", &bom(components));
+        let result = lint_config(&bom_path, &config_options(&[&config]), true, bom_options);
+        assert!(lint_config(&bom_path, &config_options(&[&config]), false, bom_options).is_ok());
+        result.map_err(|err| {
+            err.to_string()
+                .replace(&config.display().to_string(), "config.json")
+        })
+    }
+
+    #[test]
+    fn strict_fails_on_entries_that_match_no_component() {
+        let all = [
+            ("cc", "1.0.83"),
+            ("serde", "1.0.188"),
+            ("libc", "0.2.150"),
+            ("ring", "0.17.5"),
+        ];
+        assert!(lint(&all, &BomOptions::default()).is_ok());
+
+        let err = lint(&[("serde", "1.0.188")], &BomOptions::default()).unwrap_err();
+        assert_eq!(err, "3 unused entries in config.json");
+
+        let err = lint(&all[..3], &BomOptions::default()).unwrap_err();
+        assert_eq!(err, "1 unused entry in config.json");
+    }
+
+    #[test]
+    fn ignore_case_matches_entries_ignoring_case() {
+        let components = [
+            ("CC", "1.0.83"),
+            ("Serde", "1.0.188"),
+            ("libc", "0.2.150"),
+            ("ring", "0.17.5"),
+        ];
+        let err = lint(&components, &BomOptions::default()).unwrap_err();
+        assert_eq!(err, "2 unused entries in config.json");
+
+        let options = BomOptions {
+            ignore_case: true,
+            ..Default::default()
+        };
+        assert!(lint(&components, &options).is_ok());
+    }
+}
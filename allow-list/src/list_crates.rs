@@ -0,0 +1,98 @@
+use crate::cli::{BomOptions, ConfigOptions};
+use crate::licenses::{extract_deps, read_bom};
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::path::Path;
+
+/// A distributed crate at a particular version
+#[derive(Serialize)]
+struct CrateVersion {
+    name: String,
+    version: String,
+}
+
+/// Print the distributed crates and their versions, one per line or as JSON
+pub(crate) fn list_crates<W>(
+    bom_path: &Path,
+    config_options: &ConfigOptions,
+    json: bool,
+    bom_options: &BomOptions,
+    mut w: W,
+) -> Result<(), anyhow::Error>
+where
+    W: std::io::Write,
+{
+    let config = config_options.load()?;
+    let components = extract_deps(read_bom(bom_path, bom_options)?, &config, bom_options)?;
+
+    let mut crates = Vec::new();
+    for (name, dep) in components {
+        let versions: BTreeSet<_> = dep.versions.into_iter().collect();
+        for version in versions {
+            crates.push(CrateVersion {
+                name: name.clone(),
+                version: version.to_string(),
+            });
+        }
+    }
+
+    if json {
+        serde_json::to_writer_pretty(&mut w, &crates)?;
+        writeln!(w)?;
+    } else {
+        for krate in crates {
+            writeln!(w, "{} {}", krate.name, krate.version)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{bom, config_options, TempDir, CONFIG};
+
+    fn list(json: bool) -> String {
+        let dir = TempDir::new("list-crates");
+        let config = dir.write("config.json", CONFIG);
+        let bom = dir.write(
+            "bom.json",
+            &bom(&[
+                ("serde", "1.0.188"),
+                ("cc", "1.0.83"),
+                ("libc", "0.2.150"),
+                ("libc", "0.2.149"),
+            ]),
+        );
+
+        let mut out = Vec::new();
+        list_crates(
+            &bom,
+            &config_options(&[&config]),
+            json,
+            &BomOptions::default(),
+            &mut out,
+        )
+        .unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn build_only_crates_are_not_listed() {
+        assert_eq!(list(false), "libc 0.2.149\nlibc 0.2.150\nserde 1.0.188\n");
+    }
+
+    #[test]
+    fn lists_crates_as_json() {
+        let crates: serde_json::Value = serde_json::from_str(&list(true)).unwrap();
+        assert_eq!(
+            crates,
+            serde_json::json!([
+                {"name": "libc", "version": "0.2.149"},
+                {"name": "libc", "version": "0.2.150"},
+                {"name": "serde", "version": "1.0.188"},
+            ])
+        );
+    }
+}
@@ -0,0 +1,197 @@
+use crate::cli::ConfigOptions;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+/// Versions of a crate recorded in a Cargo.lock file
+#[derive(Default)]
+struct Locked {
+    versions: BTreeSet<String>,
+    /// true if any version comes from a registry or git, i.e. is not a local path dependency
+    external: bool,
+}
+
+/// Print the crates added, removed or changed between two Cargo.lock files
+///
+/// Returns an error if a crate that was added is not covered by the configuration
+pub(crate) fn lockfile_diff<W>(
+    old_path: &Path,
+    new_path: &Path,
+    config_options: &ConfigOptions,
+    mut w: W,
+) -> Result<(), anyhow::Error>
+where
+    W: std::io::Write,
+{
+    let config = config_options.load()?;
+    let old = read_lockfile(old_path)?;
+    let new = read_lockfile(new_path)?;
+
+    let mut uncovered = Vec::new();
+
+    for (name, locked) in new.iter() {
+        match old.get(name) {
+            None => {
+                let versions = join(&locked.versions);
+                if locked.external && !config.contains(name) {
+                    writeln!(w, "added: {name} {versions} (not in the allow list)")?;
+                    uncovered.push(name.as_str());
+                } else {
+                    writeln!(w, "added: {name} {versions}")?;
+                }
+            }
+            Some(previous) if previous.versions != locked.versions => {
+                writeln!(
+                    w,
+                    "changed: {name} {} -> {}",
+                    join(&previous.versions),
+                    join(&locked.versions)
+                )?;
+            }
+            Some(_) => {}
+        }
+    }
+
+    for (name, locked) in old.iter() {
+        if !new.contains_key(name) {
+            writeln!(w, "removed: {name} {}", join(&locked.versions))?;
+        }
+    }
+
+    if !uncovered.is_empty() {
+        return Err(anyhow::Error::msg(format!(
+            "{} added crate(s) not in the allow list: {}",
+            uncovered.len(),
+            uncovered.join(", ")
+        )));
+    }
+
+    Ok(())
+}
+
+fn join(versions: &BTreeSet<String>) -> String {
+    versions.iter().cloned().collect::<Vec<_>>().join(", ")
+}
+
+/// A `[[package]]` entry of a Cargo.lock file while it is being read
+#[derive(Default)]
+struct Entry {
+    name: Option<String>,
+    version: Option<String>,
+    external: bool,
+}
+
+/// Read the `[[package]]` entries of a Cargo.lock file
+fn read_lockfile(path: &Path) -> Result<BTreeMap<String, Locked>, anyhow::Error> {
+    let text = std::fs::read_to_string(path)?;
+
+    let mut entries = Vec::new();
+    let mut current: Option<Entry> = None;
+    for line in text.lines().map(str::trim) {
+        if line.starts_with('[') {
+            entries.extend(current.take());
+            if line == "[[package]]" {
+                current = Some(Entry::default());
+            }
+            continue;
+        }
+
+        let (entry, (key, value)) = match (current.as_mut(), line.split_once('=')) {
+            (Some(entry), Some(pair)) => (entry, pair),
+            _ => continue,
+        };
+        let value = value.trim().trim_matches('"').to_string();
+        match key.trim() {
+            "name" => entry.name = Some(value),
+            "version" => entry.version = Some(value),
+            "source" => entry.external = true,
+            _ => {}
+        }
+    }
+    entries.extend(current);
+
+    let mut packages: BTreeMap<String, Locked> = BTreeMap::new();
+    for entry in entries {
+        let (name, version) = entry.name.zip(entry.version).ok_or_else(|| {
+            anyhow::Error::msg(format!(
+                "Package without a name or version in {}",
+                path.display()
+            ))
+        })?;
+        let locked = packages.entry(name).or_default();
+        locked.versions.insert(version);
+        locked.external |= entry.external;
+    }
+
+    Ok(packages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{config_options, TempDir, CONFIG};
+
+    const REGISTRY: &str = "registry+https://github.com/rust-lang/crates.io-index";
+
+    fn lockfile(packages: &[(&str, &str, bool)]) -> String {
+        let mut text =
+            "# This file is automatically @generated by Cargo.\nversion = 3\n".to_string();
+        for (name, version, external) in packages {
+            text.push_str(&format!(
+                "\n[[package]]\nname = \"{name}\"\nversion = \"{version}\"\n"
+            ));
+            if *external {
+                text.push_str(&format!("source = \"{REGISTRY}\"\n"));
+            }
+            text.push_str("dependencies = [\n \"libc\",\n]\n");
+        }
+        text
+    }
+
+    fn diff(old: &str, new: &str) -> (String, Result<(), anyhow::Error>) {
+        let dir = TempDir::new("lockfile-diff");
+        let config = dir.write("config.json", CONFIG);
+        let old = dir.write("old.lock", old);
+        let new = dir.write("new.lock", new);
+        let mut out = Vec::new();
+        let result = lockfile_diff(&old, &new, &config_options(&[&config]), &mut out);
+        (String::from_utf8(out).unwrap(), result)
+    }
+
+    #[test]
+    fn reports_added_removed_and_changed_crates() {
+        let old = lockfile(&[
+            ("app", "0.1.0", false),
+            ("libc", "0.2.149", true),
+            ("ring", "0.17.5", true),
+        ]);
+        let new = lockfile(&[
+            ("app", "0.1.0", false),
+            ("libc", "0.2.150", true),
+            ("serde", "1.0.188", true),
+            ("tool", "0.1.0", false),
+        ]);
+
+        let (out, result) = diff(&old, &new);
+        assert!(result.is_ok());
+        assert_eq!(
+            out,
+            "changed: libc 0.2.149 -> 0.2.150\n\
+             added: serde 1.0.188\n\
+             added: tool 0.1.0\n\
+             removed: ring 0.17.5\n"
+        );
+    }
+
+    #[test]
+    fn gaining_an_uncovered_dependency_fails() {
+        let old = lockfile(&[("libc", "0.2.150", true)]);
+        let new = lockfile(&[("libc", "0.2.150", true), ("tokio", "1.35.0", true)]);
+
+        let (out, result) = diff(&old, &new);
+        assert_eq!(out, "added: tokio 1.35.0 (not in the allow list)\n");
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "1 added crate(s) not in the allow list: tokio"
+        );
+    }
+}
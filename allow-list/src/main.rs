@@ -1,25 +1,220 @@
 use crate::cli::*;
-use std::io::stdout;
+use std::io::{stdout, BufWriter, Write};
+use std::path::Path;
 
+pub(crate) mod bom_info;
 pub(crate) mod cli;
+pub(crate) mod compat;
 /// json configuration structures
 pub mod config;
+pub(crate) mod config_schema;
+pub(crate) mod copyright;
+pub(crate) mod diff_config;
+pub(crate) mod encoding;
+pub(crate) mod explain;
+pub(crate) mod expression;
+pub(crate) mod fmt_config;
+pub(crate) mod gen_config;
+pub(crate) mod html;
 pub(crate) mod licenses;
+pub(crate) mod lint_config;
+pub(crate) mod list_crates;
+pub(crate) mod lockfile_diff;
+pub(crate) mod merge_boms;
+pub(crate) mod reconcile;
+pub(crate) mod report;
+pub(crate) mod rst;
+#[cfg(test)]
+pub(crate) mod test_util;
+#[cfg(feature = "network")]
+pub(crate) mod urls;
+pub(crate) mod warnings;
 
 fn main() -> Result<(), anyhow::Error> {
     use clap::Parser;
 
     let cli = crate::Cli::parse();
+    let werror = cli.werror;
+    warnings::init_color(cli.no_color);
+    warnings::init_verbosity(cli.verbose);
 
     match cli.command {
         Commands::GenLicenses {
             bom_path,
-            config_path,
-        } => licenses::gen_licenses(&bom_path, &config_path, stdout()),
+            config_options,
+            output,
+            bom_options,
+            options,
+        } => write_output(output.as_deref(), |w| {
+            licenses::gen_licenses(&bom_path, &config_options, &bom_options, &options, w)
+        }),
         Commands::GenLicensesDir {
             list_dir,
             bom_file,
-            config_path,
-        } => licenses::gen_licenses_in_dirs(&list_dir, &bom_file, &config_path, stdout()),
+            config_options,
+            output,
+            dir_options,
+            bom_options,
+            options,
+        } => write_output(output.as_deref(), |w| {
+            licenses::gen_licenses_in_dirs(
+                &list_dir,
+                &bom_file,
+                &config_options,
+                &dir_options,
+                &bom_options,
+                &options,
+                w,
+            )
+        }),
+        Commands::GenAll {
+            list_dir,
+            bom_file,
+            config_options,
+            output_dir,
+            bom_options,
+            options,
+        } => licenses::gen_all(
+            &list_dir,
+            &bom_file,
+            &config_options,
+            &output_dir,
+            &bom_options,
+            &options,
+        ),
+        Commands::GenConfigFromBom { bom_path, merge } => {
+            gen_config::gen_config_from_bom(&bom_path, merge.as_deref(), stdout())
+        }
+        Commands::CheckBom {
+            bom_path,
+            config_options,
+            bom_options,
+            checks,
+        } => licenses::check_bom(&bom_path, &config_options, &bom_options, &checks, stdout()),
+        Commands::LintConfig {
+            bom_path,
+            config_options,
+            strict,
+            bom_options,
+        } => lint_config::lint_config(&bom_path, &config_options, strict, &bom_options),
+        Commands::MergeBoms {
+            bom_paths,
+            bom_options,
+        } => merge_boms::merge_boms(&bom_paths, &bom_options, stdout()),
+        Commands::Reconcile {
+            bom_path,
+            config_options,
+            strict,
+            bom_options,
+        } => reconcile::reconcile(&bom_path, &config_options, strict, &bom_options),
+        Commands::Explain {
+            bom_path,
+            config_options,
+            crate_name,
+            bom_options,
+            checks,
+        } => explain::explain(
+            &bom_path,
+            &config_options,
+            &crate_name,
+            &bom_options,
+            &checks,
+            stdout(),
+        ),
+        Commands::BomInfo { bom_path } => bom_info::bom_info(&bom_path, stdout()),
+        Commands::DiffConfig {
+            old_path,
+            new_path,
+            check,
+        } => diff_config::diff_config(&old_path, &new_path, check, stdout()),
+        Commands::LockfileDiff {
+            old_path,
+            new_path,
+            config_options,
+        } => lockfile_diff::lockfile_diff(&old_path, &new_path, &config_options, stdout()),
+        Commands::ConfigSchema => config_schema::config_schema(stdout()),
+        Commands::FmtConfig { config_path, check } => {
+            fmt_config::fmt_config(&config_path, check, stdout())
+        }
+        Commands::CheckCompat {
+            bom_path,
+            config_options,
+            product_license,
+            bom_options,
+        } => compat::check_compat(
+            &bom_path,
+            &config_options,
+            &product_license,
+            &bom_options,
+            stdout(),
+        ),
+        Commands::ListCrates {
+            bom_path,
+            config_options,
+            json,
+            bom_options,
+        } => list_crates::list_crates(&bom_path, &config_options, json, &bom_options, stdout()),
+    }?;
+
+    warnings::check(werror)
+}
+
+/// Generate a report into the file at `path` if provided, to stdout otherwise
+///
+/// The report is written to a temporary file next to `path` that only replaces it once the report
+/// is complete, so that a failure leaves any previous report in place
+pub(crate) fn write_output<F>(path: Option<&Path>, generate: F) -> Result<(), anyhow::Error>
+where
+    F: FnOnce(&mut dyn Write) -> Result<(), anyhow::Error>,
+{
+    let Some(path) = path else {
+        return generate(&mut stdout());
+    };
+    let name = path
+        .file_name()
+        .ok_or_else(|| anyhow::Error::msg(format!("{} is not a file", path.display())))?;
+    let temp = path.with_file_name(format!(
+        ".{}.{}.tmp",
+        name.to_string_lossy(),
+        std::process::id()
+    ));
+
+    let result = std::fs::File::create(&temp)
+        .map_err(anyhow::Error::from)
+        .and_then(|file| {
+            let mut w = BufWriter::new(file);
+            generate(&mut w)?;
+            w.into_inner().map_err(|err| err.into_error())?;
+            Ok(())
+        })
+        .and_then(|_| Ok(std::fs::rename(&temp, path)?));
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    #[test]
+    fn output_is_replaced_once_the_report_is_complete() {
+        let dir = TempDir::new("write-output");
+        let path = dir.write("report.txt", "previous report\n");
+
+        let result = write_output(Some(&path), |w| {
+            writeln!(w, "partial")?;
+            Err(anyhow::Error::msg("invalid configuration"))
+        });
+        assert_eq!(result.unwrap_err().to_string(), "invalid configuration");
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "previous report\n");
+
+        write_output(Some(&path), |w| Ok(writeln!(w, "new report")?)).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "new report\n");
+
+        // no temporary file is left behind
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
     }
 }
@@ -0,0 +1,259 @@
+use crate::cli::BomOptions;
+use crate::licenses::read_bom;
+use crate::warnings;
+use cyclonedx_bom::external_models::date_time::DateTime;
+use cyclonedx_bom::models::component::{Component, Components};
+use cyclonedx_bom::models::metadata::Metadata;
+use cyclonedx_bom::models::tool::{Tool, Tools};
+use cyclonedx_bom::prelude::Bom;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// Write a CycloneDX 1.4 JSON BOM whose components are the union of those of every BOM
+///
+/// Components with the same name and version are listed once, in the order they are first found,
+/// with the licenses declared by any of the BOMs
+pub(crate) fn merge_boms<W>(
+    bom_paths: &[PathBuf],
+    bom_options: &BomOptions,
+    w: W,
+) -> Result<(), anyhow::Error>
+where
+    W: std::io::Write,
+{
+    let epoch = std::env::var("SOURCE_DATE_EPOCH").ok();
+    write_merged(bom_paths, bom_options, epoch.as_deref(), w)
+}
+
+/// Write the merged BOM, reproducibly if the time of the build is given as `epoch`
+fn write_merged<W>(
+    bom_paths: &[PathBuf],
+    bom_options: &BomOptions,
+    epoch: Option<&str>,
+    mut w: W,
+) -> Result<(), anyhow::Error>
+where
+    W: std::io::Write,
+{
+    let mut components: Vec<Component> = Vec::new();
+    let mut index: BTreeMap<(String, String), usize> = BTreeMap::new();
+
+    for path in bom_paths {
+        let bom = read_bom(path, bom_options)?;
+        for component in bom.components.into_iter().flat_map(|x| x.0) {
+            let key = (
+                component.name.to_string(),
+                component
+                    .version
+                    .as_ref()
+                    .map(|x| x.to_string())
+                    .unwrap_or_default(),
+            );
+            match index.get(&key) {
+                Some(&i) => merge_licenses(&mut components[i], component, path),
+                None => {
+                    index.insert(key, components.len());
+                    components.push(component);
+                }
+            }
+        }
+    }
+
+    let mut metadata = match epoch {
+        Some(epoch) => Metadata {
+            timestamp: Some(source_date(epoch)?),
+            ..Metadata::default()
+        },
+        None => Metadata::new()
+            .map_err(|err| anyhow::Error::msg(format!("Unable to create the metadata: {err}")))?,
+    };
+    metadata.tools = Some(Tools(vec![Tool::new(
+        "stepfunc",
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION"),
+    )]));
+
+    let mut bom = Bom {
+        metadata: Some(metadata),
+        components: Some(Components(components)),
+        ..Bom::default()
+    };
+    // a random serial number would make reproducible builds differ
+    if epoch.is_some() {
+        bom.serial_number = None;
+    }
+    let mut json = Vec::new();
+    bom.output_as_json_v1_4(&mut json)
+        .map_err(|err| anyhow::Error::msg(format!("Unable to write the merged BOM: {err}")))?;
+    // the serial number is optional, but must be a URN when present
+    let mut json: serde_json::Value = serde_json::from_slice(&json)?;
+    if let Some(fields) = json.as_object_mut() {
+        fields.retain(|_, value| !value.is_null());
+    }
+    serde_json::to_writer_pretty(&mut w, &json)?;
+    writeln!(w)?;
+
+    Ok(())
+}
+
+/// Timestamp of a reproducible build, given as the number of seconds since the Unix epoch
+///
+/// See <https://reproducible-builds.org/specs/source-date-epoch/>
+fn source_date(epoch: &str) -> Result<DateTime, anyhow::Error> {
+    let seconds: i64 = epoch.trim().parse().map_err(|_| {
+        anyhow::Error::msg(format!(
+            "SOURCE_DATE_EPOCH must be a number of seconds but is '{epoch}'"
+        ))
+    })?;
+    let (days, time) = (seconds.div_euclid(86400), seconds.rem_euclid(86400));
+
+    // civil date from the number of days since 1970-01-01, in the proleptic Gregorian calendar
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    let timestamp = format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        time / 3600,
+        time % 3600 / 60,
+        time % 60
+    );
+    DateTime::try_from(timestamp)
+        .map_err(|err| anyhow::Error::msg(format!("Invalid SOURCE_DATE_EPOCH {epoch}: {err}")))
+}
+
+/// Add the licenses of `other` that `existing` does not declare, warning if both declare some
+fn merge_licenses(existing: &mut Component, other: Component, path: &std::path::Path) {
+    let Some(licenses) = other.licenses else {
+        return;
+    };
+    let Some(merged) = existing.licenses.as_mut() else {
+        existing.licenses = Some(licenses);
+        return;
+    };
+    let added: Vec<_> = licenses
+        .0
+        .into_iter()
+        .filter(|x| !merged.0.contains(x))
+        .collect();
+    if !added.is_empty() {
+        warnings::warn(format!(
+            "{} {} declares other licenses in {}, keeping all of them",
+            existing.name,
+            existing
+                .version
+                .as_ref()
+                .map(|x| x.to_string())
+                .unwrap_or_default(),
+            path.display()
+        ));
+        merged.0.extend(added);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{bom, bom_with, component, TempDir};
+
+    fn merge(dir: &TempDir, epoch: Option<&str>) -> serde_json::Value {
+        let paths = [
+            dir.write("a.json", &bom(&[("libc", "0.2.150"), ("serde", "1.0.188")])),
+            dir.write("b.json", &bom(&[("serde", "1.0.188"), ("ring", "0.17.5")])),
+        ];
+        let mut out = Vec::new();
+        write_merged(&paths, &BomOptions::default(), epoch, &mut out).unwrap();
+        serde_json::from_slice(&out).unwrap()
+    }
+
+    #[test]
+    fn merges_components_by_name_and_version() {
+        let dir = TempDir::new("merge-boms");
+        let merged = merge(&dir, None);
+
+        let names: Vec<&str> = merged["components"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|x| x["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, ["libc", "serde", "ring"]);
+        assert!(merged["serialNumber"]
+            .as_str()
+            .unwrap()
+            .starts_with("urn:uuid:"));
+    }
+
+    #[test]
+    fn merges_the_licenses_declared_by_any_bom() {
+        let licensed = |name: &str, version: &str, spdx: &[&str]| {
+            let mut component = component(name, version);
+            component["licenses"] = spdx
+                .iter()
+                .map(|x| serde_json::json!({"license": {"id": x}}))
+                .collect();
+            component
+        };
+        let dir = TempDir::new("merge-boms-licenses");
+        let paths = [
+            dir.write(
+                "a.json",
+                &bom_with(vec![
+                    licensed("serde", "1.0.188", &["MIT"]),
+                    component("libc", "0.2.150"),
+                ]),
+            ),
+            dir.write(
+                "b.json",
+                &bom_with(vec![
+                    licensed("serde", "1.0.188", &["MIT", "Apache-2.0"]),
+                    licensed("libc", "0.2.150", &["MIT"]),
+                ]),
+            ),
+        ];
+        let mut out = Vec::new();
+        write_merged(&paths, &BomOptions::default(), Some("0"), &mut out).unwrap();
+        let merged: serde_json::Value = serde_json::from_slice(&out).unwrap();
+
+        let licenses = |i: usize| -> Vec<&str> {
+            merged["components"][i]["licenses"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|x| x["license"]["id"].as_str().unwrap())
+                .collect()
+        };
+        assert_eq!(merged["components"].as_array().unwrap().len(), 2);
+        assert_eq!(licenses(0), ["MIT", "Apache-2.0"]);
+        assert_eq!(licenses(1), ["MIT"]);
+    }
+
+    #[test]
+    fn source_date_epoch_makes_the_output_reproducible() {
+        let dir = TempDir::new("merge-boms-epoch");
+        let first = merge(&dir, Some("1700000000"));
+
+        assert_eq!(first, merge(&dir, Some("1700000000")));
+        assert_eq!(first["metadata"]["timestamp"], "2023-11-14T22:13:20Z");
+        assert!(first.get("serialNumber").is_none());
+    }
+
+    #[test]
+    fn source_date_converts_seconds_to_a_utc_timestamp() {
+        assert_eq!(
+            source_date("0").unwrap().to_string(),
+            "1970-01-01T00:00:00Z"
+        );
+        assert_eq!(
+            source_date("951782400").unwrap().to_string(),
+            "2000-02-29T00:00:00Z"
+        );
+        assert!(source_date("yesterday").is_err());
+    }
+}
@@ -0,0 +1,108 @@
+use crate::cli::{BomOptions, ConfigOptions};
+use crate::licenses::{declared_expression, read_bom};
+use crate::warnings;
+use std::path::Path;
+
+/// Warn about every component of the BOM that declares a license other than the one in the configuration
+///
+/// Components that are not in `third_party` or that do not declare SPDX licenses are not compared.
+/// If `strict` is set, an error is returned if any license differs.
+pub(crate) fn reconcile(
+    bom_path: &Path,
+    config_options: &ConfigOptions,
+    strict: bool,
+    bom_options: &BomOptions,
+) -> Result<(), anyhow::Error> {
+    let config = config_options.load()?;
+    let bom = read_bom(bom_path, bom_options)?;
+
+    let mut divergences = 0;
+    for component in bom.components.iter().flat_map(|x| x.0.iter()) {
+        let name = component.name.to_string();
+        let key = if config.contains(&name) {
+            Some(name.as_str())
+        } else {
+            config
+                .find_ignore_case(&name)
+                .filter(|_| bom_options.ignore_case)
+        };
+        let Some(pkg) = key.and_then(|x| config.third_party.get(x)) else {
+            continue;
+        };
+        let version = component
+            .version
+            .as_ref()
+            .map(|x| x.to_string())
+            .unwrap_or_default();
+        let Some(declared) = declared_expression(component) else {
+            warnings::debug(format!(
+                "{name} {version} does not declare SPDX licenses in the BOM"
+            ));
+            continue;
+        };
+
+        let asserted = pkg.offered_expression();
+        if asserted.normalized() != declared.normalized() {
+            warnings::warn(format!(
+                "{name} {version} is licensed under {asserted} according to the configuration, but the BOM declares {declared}"
+            ));
+            divergences += 1;
+        }
+    }
+
+    if strict && divergences > 0 {
+        return Err(anyhow::Error::msg(format!(
+            "{divergences} divergence{} between {} and the BOM",
+            if divergences == 1 { "" } else { "s" },
+            config_options.describe()
+        )));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{bom_with, component, config_options, TempDir, CONFIG};
+
+    fn reconcile_with(serde_license: serde_json::Value) -> Result<(), String> {
+        let mut serde = component("serde", "1.0.188");
+        serde["licenses"] = serde_license;
+        let mut ring = component("ring", "0.17.5");
+        ring["licenses"] = serde_json::json!([{"expression": "ISC AND OpenSSL"}]);
+        let bom = bom_with(vec![serde, ring, component("libc", "0.2.150")]);
+
+        let dir = TempDir::new("reconcile");
+        let config = dir.write("config.json", CONFIG);
+        let bom_path = dir.write("bom.json", &bom);
+        assert!(reconcile(
+            &bom_path,
+            &config_options(&[&config]),
+            false,
+            &BomOptions::default()
+        )
+        .is_ok());
+        reconcile(
+            &bom_path,
+            &config_options(&[&config]),
+            true,
+            &BomOptions::default(),
+        )
+        .map_err(|err| {
+            err.to_string()
+                .replace(&config.display().to_string(), "config.json")
+        })
+    }
+
+    #[test]
+    fn strict_fails_when_the_bom_declares_another_license() {
+        assert!(reconcile_with(serde_json::json!([{"license": {"id": "MIT"}}])).is_ok());
+
+        let err = reconcile_with(serde_json::json!([{"license": {"id": "Apache-2.0"}}]));
+        assert_eq!(
+            err.unwrap_err(),
+            "1 divergence between config.json and the BOM"
+        );
+    }
+}
@@ -0,0 +1,933 @@
+use crate::cli::{Format, ReportOptions, SortBy};
+use crate::compat;
+use crate::config::{Config, LicenseInfo, Source};
+use crate::copyright;
+use crate::html;
+use crate::licenses::{check_components, write_json, Dependency, Targets};
+use crate::rst;
+use crate::warnings;
+use semver::Version;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+/// The content of a license report, independent of where and how it is written
+pub(crate) struct Report<'a> {
+    /// every license used by a distributed crate, keyed by SPDX id
+    pub(crate) licenses: BTreeMap<&'a str, LicenseInfo<'a>>,
+    /// SPDX ids of the licenses whose text is left out of the report
+    pub(crate) omitted: Vec<&'a str>,
+    /// the distributed crates, in the order they are reported
+    pub(crate) crates: Vec<CrateEntry<'a>>,
+    /// product the report is generated for, if it is named
+    pub(crate) subject: Option<&'a str>,
+    /// distinct NOTICE files that must be preserved, in the order they are first found
+    pub(crate) notices: Vec<Notice<'a>>,
+    /// number of crates distributed under each license, if the counts are reported
+    pub(crate) counts: Option<BTreeMap<&'a str, usize>>,
+    /// whether the key obligations of each license are reported
+    pub(crate) show_obligations: bool,
+}
+
+/// A NOTICE file and the crates that provide it
+pub(crate) struct Notice<'a> {
+    /// names of the crates whose license comes with this NOTICE
+    pub(crate) crates: Vec<&'a str>,
+    /// lines of the NOTICE
+    pub(crate) lines: &'a [String],
+}
+
+/// Opening sentence of every report that does not name its subject
+const INTRO: &str =
+    "This distribution contains open source dependencies under the following licenses:";
+
+/// Text of a license as it is provided with the report
+pub(crate) enum LicenseText<'a> {
+    /// the text is included in the report
+    Inline(Cow<'a, str>),
+    /// the text was written to this file
+    File(PathBuf),
+}
+
+/// Everything the report says about a single crate
+pub(crate) struct CrateEntry<'a> {
+    /// name of the crate, i.e. its key in the configuration
+    pub(crate) id: &'a str,
+    /// versions as they are printed
+    pub(crate) versions: Vec<String>,
+    /// targets the crate applies to as they are printed, if they are reported
+    pub(crate) targets: Option<String>,
+    /// versions of the crate in each target as they are printed, if they are reported
+    pub(crate) target_versions: Vec<(&'a str, String)>,
+    /// where the crate came from
+    pub(crate) source: &'a Source,
+    /// where information about the crate can be found
+    pub(crate) url: String,
+    /// license expression of the crate as it is printed
+    pub(crate) expression: String,
+    /// why the license was chosen among OR licenses
+    pub(crate) rationale: Option<&'a str>,
+    /// copyright lines from the configuration
+    pub(crate) copyright: Vec<String>,
+    /// true if the author(s) provided an actual copyright statement for at least one license
+    pub(crate) has_copyright: bool,
+    /// note from the reviewers
+    pub(crate) note: Option<&'a str>,
+    /// authors from the BOM, used when the configuration has no copyright statement
+    pub(crate) attribution: Vec<&'a str>,
+}
+
+impl<'a> Report<'a> {
+    /// Build a report for the distributed components, failing on any problem that would make it incomplete
+    pub(crate) fn new(
+        components: &'a BTreeMap<String, Dependency>,
+        config: &'a Config,
+        options: &ReportOptions,
+    ) -> Result<Self, anyhow::Error> {
+        let problems = check_components(components, config, &options.checks);
+        if !problems.is_empty() {
+            return Err(anyhow::Error::msg(problems.join("\n")));
+        }
+
+        let mut ordered: Vec<(&String, &Dependency)> = components.iter().collect();
+        if options.sort_by == SortBy::Risk {
+            // stable sort keeps the alphabetical order within each category
+            ordered.sort_by_key(|(name, _)| {
+                std::cmp::Reverse(config.third_party.get(*name).map(compat::risk))
+            });
+        }
+
+        let mut licenses: BTreeMap<&str, LicenseInfo> = BTreeMap::new();
+        let mut crates = Vec::new();
+        let mut notices: Vec<Notice> = Vec::new();
+        // licenses whose obligations are fulfilled, i.e. only the first alternative of an OR
+        let mut fulfilled: BTreeSet<String> = BTreeSet::new();
+        let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+        for (name, dep) in ordered {
+            let pkg = config.third_party.get(name).ok_or_else(|| {
+                anyhow::Error::msg(format!("3rd party package {name} not in the allow list"))
+            })?;
+
+            for license in pkg.effective_licenses() {
+                licenses.insert(license.spdx_short(), license.info());
+                *counts.entry(license.spdx_short()).or_default() += 1;
+                if let Some(lines) = license.notice() {
+                    // identical NOTICE files are only printed once
+                    match notices.iter_mut().find(|x| x.lines == lines) {
+                        Some(notice) => notice.crates.push(name),
+                        None => notices.push(Notice {
+                            crates: vec![name],
+                            lines,
+                        }),
+                    }
+                }
+            }
+
+            fulfilled.extend(
+                pkg.expression()
+                    .first_alternative()
+                    .into_iter()
+                    .map(|x| x.to_string()),
+            );
+
+            let versions = if options.collapse_versions {
+                collapse_versions(&dep.versions)
+            } else {
+                dep.versions.iter().map(|x| x.to_string()).collect()
+            };
+
+            let mut copyright = Vec::new();
+            let mut missing = Vec::new();
+            for lic in pkg.effective_licenses() {
+                if lic.is_copyright_missing() {
+                    missing.push(lic.spdx_short());
+                }
+                for line in lic
+                    .copyright(config.copyright_placeholder())
+                    .into_iter()
+                    .flatten()
+                {
+                    copyright.push(if options.normalize_copyright {
+                        copyright::normalize(&line)
+                    } else {
+                        line
+                    });
+                }
+            }
+
+            if !missing.is_empty() {
+                warnings::warn(format!(
+                    "no copyright statement for the {} license(s) of {name}",
+                    missing.join(", ")
+                ));
+            }
+
+            // fall back to the attribution in the BOM if the config has no copyright statement
+            let has_copyright = pkg.effective_licenses().any(|x| x.has_copyright());
+            let attribution = if has_copyright {
+                Vec::new()
+            } else {
+                dep.authors.iter().map(|x| x.as_str()).collect()
+            };
+
+            let targets = dep.targets.as_ref().map(|x| match x {
+                Targets::All => "all".to_string(),
+                Targets::Only(targets) => targets.iter().cloned().collect::<Vec<_>>().join(", "),
+            });
+
+            let target_versions = dep
+                .target_versions
+                .iter()
+                .flatten()
+                .map(|(target, versions)| {
+                    let versions: Vec<String> = versions.iter().map(|x| x.to_string()).collect();
+                    (target.as_str(), versions.join(", "))
+                })
+                .collect();
+
+            crates.push(CrateEntry {
+                id: name,
+                versions,
+                targets,
+                target_versions,
+                source: &pkg.source,
+                url: pkg.url(),
+                expression: pkg.expression().to_string(),
+                rationale: pkg.chosen_rationale.as_deref(),
+                copyright,
+                has_copyright,
+                note: pkg.comment.as_deref(),
+                attribution,
+            });
+        }
+
+        let omitted = licenses
+            .keys()
+            .copied()
+            .filter(|x| options.omit_text.iter().any(|y| y == x) || !fulfilled.contains(*x))
+            .collect();
+
+        Ok(Self {
+            licenses,
+            omitted,
+            crates,
+            subject: None,
+            notices,
+            counts: options.license_counts.then_some(counts),
+            show_obligations: options.show_obligations,
+        })
+    }
+
+    /// SPDX id of a license as it is listed in the header, followed by the number of crates if it is reported
+    pub(crate) fn license_label(&self, spdx: &str) -> String {
+        match self.counts.as_ref().and_then(|x| x.get(spdx)) {
+            Some(1) => format!("{spdx} (1 crate)"),
+            Some(count) => format!("{spdx} ({count} crates)"),
+            None => spdx.to_string(),
+        }
+    }
+
+    /// Key obligations of a license as they are listed in the header, if they are reported
+    pub(crate) fn obligations(&self, spdx: &str) -> Option<String> {
+        if !self.show_obligations {
+            return None;
+        }
+        Some(match compat::obligations(spdx) {
+            None => "unknown, review the license text".to_string(),
+            Some(obligations) if obligations.is_empty() => "none".to_string(),
+            Some(obligations) => obligations.join(", "),
+        })
+    }
+
+    /// Opening sentence of the report
+    pub(crate) fn intro(&self) -> Cow<'static, str> {
+        match self.subject {
+            Some(subject) => Cow::Owned(format!(
+                "This distribution of {subject} contains open source dependencies under the following licenses:"
+            )),
+            None => Cow::Borrowed(INTRO),
+        }
+    }
+
+    /// Write the header and the per-crate information in the requested format
+    ///
+    /// `location` completes the sentence telling the reader where the license texts are provided
+    pub(crate) fn write_summary<W>(
+        &self,
+        w: &mut W,
+        format: Format,
+        wrap: Option<usize>,
+        location: &str,
+    ) -> std::io::Result<()>
+    where
+        W: std::io::Write,
+    {
+        match format {
+            Format::Text => self.write_plain_summary(w, wrap, location),
+            Format::Rst => rst::write_summary(self, w, location),
+            Format::Html => html::write_summary(self, w, location),
+            Format::Json => write_json(self, w),
+        }
+    }
+
+    /// Write the full text of every license that is not omitted in the requested format
+    ///
+    /// If `license_dir` is provided, each text is written to its own file in that directory
+    /// and only a reference to the file is written to `w`
+    pub(crate) fn write_texts<W>(
+        &self,
+        w: &mut W,
+        format: Format,
+        license_dir: Option<&Path>,
+    ) -> std::io::Result<()>
+    where
+        W: std::io::Write,
+    {
+        let texts = self.texts(license_dir)?;
+        match format {
+            Format::Text => {
+                write_plain_texts(w, &texts)?;
+                write_plain_notices(w, &self.notices)
+            }
+            Format::Rst => {
+                rst::write_texts(w, &texts)?;
+                rst::write_notices(w, &self.notices)
+            }
+            Format::Html => {
+                html::write_texts(w, &texts)?;
+                html::write_notices(w, &self.notices)
+            }
+            // the JSON document is complete after the summary
+            Format::Json => Ok(()),
+        }
+    }
+
+    /// Sentence telling the reader where copies of the licenses are provided
+    pub(crate) fn copies_sentence(&self, location: &str) -> String {
+        if self.omitted.is_empty() {
+            format!("Copies of these licenses are provided {location}. They may also be obtained from the URLs above.")
+        } else {
+            format!("Copies of these licenses, except {}, are provided {location}. All of them may be obtained from the URLs above.", self.omitted.join(", "))
+        }
+    }
+
+    /// Texts of the licenses that are not omitted, written to files in `license_dir` if provided
+    fn texts(
+        &self,
+        license_dir: Option<&Path>,
+    ) -> std::io::Result<Vec<(&'a str, LicenseText<'a>)>> {
+        if let Some(dir) = license_dir {
+            std::fs::create_dir_all(dir)?;
+        }
+        let mut texts = Vec::new();
+        for (spdx, info) in self.licenses.iter() {
+            if self.omitted.contains(spdx) {
+                continue;
+            }
+            let text = match license_dir {
+                Some(dir) => {
+                    let path = dir.join(format!("{spdx}.txt"));
+                    std::fs::write(&path, info.text.as_bytes())?;
+                    LicenseText::File(path)
+                }
+                None => LicenseText::Inline(info.text.clone()),
+            };
+            texts.push((*spdx, text));
+        }
+        Ok(texts)
+    }
+
+    fn write_plain_summary<W>(
+        &self,
+        w: &mut W,
+        wrap: Option<usize>,
+        location: &str,
+    ) -> std::io::Result<()>
+    where
+        W: std::io::Write,
+    {
+        write_prose(w, &self.intro(), wrap)?;
+        writeln!(w)?;
+        for (spdx, info) in self.licenses.iter() {
+            writeln!(w, "  * {}", self.license_label(spdx))?;
+            writeln!(w, "      - {}", info.url)?;
+            if let Some(note) = info.note {
+                writeln!(w, "      - note: {note}")?;
+            }
+            if let Some(obligations) = self.obligations(spdx) {
+                writeln!(w, "      - obligations: {obligations}")?;
+            }
+        }
+        writeln!(w)?;
+        write_prose(w, &self.copies_sentence(location), wrap)?;
+        writeln!(w)?;
+
+        for entry in self.crates.iter() {
+            writeln!(w, "crate: {}", entry.id)?;
+            writeln!(w, "version(s): {}", entry.versions.join(", "))?;
+            if let Some(targets) = &entry.targets {
+                writeln!(w, "target(s): {targets}")?;
+            }
+            for (target, versions) in entry.target_versions.iter() {
+                writeln!(w, "version(s) in {target}: {versions}")?;
+            }
+            writeln!(w, "url: {}", entry.url)?;
+            writeln!(w, "license(s): {}", entry.expression)?;
+            if let Some(rationale) = entry.rationale {
+                write_prose(w, &format!("rationale: {rationale}"), wrap)?;
+            }
+            for line in entry.copyright.iter() {
+                write_prose(w, line, wrap)?;
+            }
+            if let Some(note) = entry.note {
+                write_prose(w, &format!("note: {note}"), wrap)?;
+            }
+            for author in entry.attribution.iter() {
+                write_prose(w, &format!("attribution: {author}"), wrap)?;
+            }
+            writeln!(w)?;
+        }
+
+        Ok(())
+    }
+
+    /// Write the copyright and attribution lines of every crate, each line once per crate
+    pub(crate) fn write_attributions<W>(&self, w: &mut W) -> std::io::Result<()>
+    where
+        W: std::io::Write,
+    {
+        for entry in self.crates.iter() {
+            let mut lines: Vec<&str> = Vec::new();
+            for line in entry
+                .copyright
+                .iter()
+                .map(|x| x.as_str())
+                .chain(entry.attribution.iter().copied())
+            {
+                if !lines.contains(&line) {
+                    lines.push(line);
+                }
+            }
+            if lines.is_empty() {
+                continue;
+            }
+            writeln!(w, "{}", entry.id)?;
+            for line in lines {
+                writeln!(w, "  {line}")?;
+            }
+            writeln!(w)?;
+        }
+        Ok(())
+    }
+
+    /// Leave the copyright and attribution lines out of the rendered report
+    pub(crate) fn omit_attributions(&mut self) {
+        for entry in self.crates.iter_mut() {
+            entry.copyright.clear();
+            entry.attribution.clear();
+        }
+    }
+
+    /// Write one CSV row per crate for use in spreadsheets
+    pub(crate) fn write_csv<W>(&self, w: &mut W) -> std::io::Result<()>
+    where
+        W: std::io::Write,
+    {
+        writeln!(w, "crate,version,licenses,source,copyright_present")?;
+        for entry in self.crates.iter() {
+            writeln!(
+                w,
+                "{},{},{},{},{}",
+                csv_field(entry.id),
+                csv_field(&entry.versions.join(", ")),
+                csv_field(&entry.expression),
+                csv_field(entry.source.name()),
+                entry.has_copyright
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Issue a HEAD request for every URL in the report, warning about any that are unreachable
+    #[cfg(feature = "network")]
+    pub(crate) fn check_urls(&self) {
+        let mut checker = crate::urls::UrlChecker::new();
+        let urls = self
+            .licenses
+            .values()
+            .map(|x| x.url)
+            .chain(self.crates.iter().map(|x| x.url.as_str()));
+        for problem in urls.filter_map(|x| checker.check(x)) {
+            warnings::warn(problem);
+        }
+    }
+}
+
+/// Write the NOTICE files after the license texts, under a banner like the ones of the texts
+fn write_plain_notices<W>(w: &mut W, notices: &[Notice]) -> std::io::Result<()>
+where
+    W: std::io::Write,
+{
+    if notices.is_empty() {
+        return Ok(());
+    }
+    writeln!(w, "{:=<79}", format!("{} NOTICE ", "=".repeat(33)))?;
+    writeln!(w)?;
+    for notice in notices {
+        writeln!(w, "NOTICE of {}:", notice.crates.join(", "))?;
+        writeln!(w)?;
+        for line in notice.lines {
+            writeln!(w, "{line}")?;
+        }
+        writeln!(w)?;
+    }
+    Ok(())
+}
+
+fn write_plain_texts<W>(w: &mut W, texts: &[(&str, LicenseText)]) -> std::io::Result<()>
+where
+    W: std::io::Write,
+{
+    for (_, text) in texts {
+        match text {
+            LicenseText::Inline(text) => writeln!(w, "{text}")?,
+            LicenseText::File(path) => writeln!(w, "See file: {}", path.display())?,
+        }
+        writeln!(w)?;
+    }
+    Ok(())
+}
+
+/// Quote a CSV field if it contains a delimiter, quote or line break
+fn csv_field(value: &str) -> Cow<'_, str> {
+    if value.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", value.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(value)
+    }
+}
+
+/// Write a line of prose, word-wrapped to `width` columns if requested
+fn write_prose<W>(w: &mut W, text: &str, width: Option<usize>) -> std::io::Result<()>
+where
+    W: std::io::Write,
+{
+    match width {
+        Some(width) => {
+            for line in wrap(text, width) {
+                writeln!(w, "{line}")?;
+            }
+            Ok(())
+        }
+        None => writeln!(w, "{text}"),
+    }
+}
+
+/// Greedy word wrap that never splits a word, so long URLs are kept intact
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        let len = line.chars().count();
+        if len > 0 && len + 1 + word.chars().count() > width {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(word);
+    }
+    if !line.is_empty() || lines.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// Summarize versions as one entry per major.minor series, e.g. "1.0.1 through 1.0.9"
+fn collapse_versions(versions: &[Version]) -> Vec<String> {
+    let mut series: BTreeMap<(u64, u64), (&Version, &Version)> = BTreeMap::new();
+    for version in versions {
+        series
+            .entry((version.major, version.minor))
+            .and_modify(|(min, max)| {
+                if version < *min {
+                    *min = version;
+                }
+                if version > *max {
+                    *max = version;
+                }
+            })
+            .or_insert((version, version));
+    }
+
+    series
+        .values()
+        .map(|(min, max)| {
+            if min == max {
+                min.to_string()
+            } else {
+                format!("{min} through {max}")
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{bom, bom_with, component, gen_report, TempDir, CONFIG};
+
+    fn versions(versions: &[&str]) -> Vec<Version> {
+        versions
+            .iter()
+            .map(|x| Version::parse(x).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn collapse_versions_summarizes_each_minor_series() {
+        assert_eq!(
+            collapse_versions(&versions(&["1.0.9", "1.0.1", "1.1.0", "1.0.4"])),
+            ["1.0.1 through 1.0.9", "1.1.0"]
+        );
+        assert_eq!(collapse_versions(&versions(&["0.2.150"])), ["0.2.150"]);
+    }
+
+    #[test]
+    fn collapse_versions_applies_to_the_report() {
+        let bom = bom(&[("serde", "1.0.1"), ("serde", "1.0.5"), ("serde", "1.0.3")]);
+
+        let report = gen_report(CONFIG, &bom, &[]).unwrap();
+        assert!(report.contains("version(s): 1.0.1, 1.0.5, 1.0.3\n"));
+
+        let report = gen_report(CONFIG, &bom, &["--collapse-versions"]).unwrap();
+        assert!(report.contains("version(s): 1.0.1 through 1.0.5\n"));
+    }
+
+    #[test]
+    fn show_obligations_annotates_the_licenses_in_the_header() {
+        let config = r#"{"build_only":[],"vendor":{},"third_party":{
+            "either":{"id":"either","source":"crates.io","licenses":[{"Apache2":{}}]},
+            "serde":{"id":"serde","source":"crates.io","licenses":[{"MIT":{"copyright":"NotPresent"}}]}}}"#;
+        let bom = bom(&[("either", "1.9.0"), ("serde", "1.0.188")]);
+
+        let report = gen_report(config, &bom, &[]).unwrap();
+        assert!(!report.contains("- obligations:"));
+
+        let report = gen_report(config, &bom, &["--show-obligations"]).unwrap();
+        assert!(report.contains(
+            "  * Apache-2.0\n      - https://spdx.org/licenses/Apache-2.0.html\n      - obligations: patent grant, attribution required\n"
+        ));
+        assert!(report.contains("      - obligations: attribution required\n"));
+    }
+
+    #[test]
+    fn license_counts_are_listed_in_the_header() {
+        let bom = bom(&[
+            ("serde", "1.0.188"),
+            ("libc", "0.2.150"),
+            ("ring", "0.17.5"),
+        ]);
+
+        let report = gen_report(CONFIG, &bom, &[]).unwrap();
+        assert!(report.contains("  * MIT\n"));
+        assert!(!report.contains("crates)"));
+
+        let report = gen_report(CONFIG, &bom, &["--license-counts"]).unwrap();
+        assert!(report.contains("  * MIT (2 crates)\n"));
+        assert!(report.contains("  * ISC (1 crate)\n"));
+        assert!(report.contains("  * OpenSSL (1 crate)\n"));
+    }
+
+    #[test]
+    fn licenses_are_joined_as_the_package_combines_them() {
+        let bom = bom(&[("ring", "0.17.5")]);
+
+        let report = gen_report(CONFIG, &bom, &[]).unwrap();
+        assert!(report.contains("license(s): OpenSSL AND ISC\n"));
+
+        let config = CONFIG.replace(r#""id": "ring","#, r#""id": "ring", "combine": "OR","#);
+        let report = gen_report(&config, &bom, &[]).unwrap();
+        assert!(report.contains("license(s): OpenSSL OR ISC\n"));
+    }
+
+    #[test]
+    fn comments_are_printed_as_notes() {
+        let bom = bom(&[("serde", "1.0.188"), ("libc", "0.2.150")]);
+        let config = CONFIG.replace(
+            r#""id": "serde","#,
+            r#""id": "serde", "comment": "approved by legal 2024-01","#,
+        );
+
+        let report = gen_report(&config, &bom, &[]).unwrap();
+        assert_eq!(report.matches("note: ").count(), 1);
+        assert!(report.contains("note: approved by legal 2024-01\n"));
+    }
+
+    fn crate_order(report: &str) -> Vec<&str> {
+        report
+            .lines()
+            .filter_map(|x| x.strip_prefix("crate: "))
+            .collect()
+    }
+
+    #[test]
+    fn sort_by_risk_lists_copyleft_crates_first() {
+        let config = CONFIG.replace(
+            r#"[{"MIT": {"copyright": {"Lines": ["Copyright (c) 2014 The Rust Project Developers"]}}}]"#,
+            r#"["MPLv2"]"#,
+        );
+        let bom = bom(&[
+            ("serde", "1.0.188"),
+            ("libc", "0.2.150"),
+            ("ring", "0.17.5"),
+        ]);
+
+        let report = gen_report(&config, &bom, &[]).unwrap();
+        assert_eq!(crate_order(&report), ["libc", "ring", "serde"]);
+
+        let report = gen_report(&config, &bom, &["--sort-by", "risk"]).unwrap();
+        assert_eq!(crate_order(&report), ["serde", "libc", "ring"]);
+    }
+
+    #[test]
+    fn omit_text_leaves_out_the_text_but_keeps_the_reference() {
+        let config = CONFIG.replace(r#"[{"MIT": {"copyright": "NotPresent"}}]"#, r#"["MPLv2"]"#);
+        let bom = bom(&[("serde", "1.0.188"), ("libc", "0.2.150")]);
+
+        let report = gen_report(&config, &bom, &[]).unwrap();
+        assert!(report.contains("==== MIT ===="));
+
+        let report = gen_report(&config, &bom, &["--omit-text", "MIT"]).unwrap();
+        assert!(report.contains("license(s): MIT\n"));
+        assert!(report.contains("except MIT,"));
+        assert!(!report.contains("==== MIT ===="));
+        assert!(report.contains("==== MPL-2.0 ===="));
+    }
+
+    #[test]
+    fn wrap_never_splits_words() {
+        assert_eq!(
+            wrap("see https://example.com/a/very/long/url for details", 12),
+            ["see", "https://example.com/a/very/long/url", "for details"]
+        );
+        assert_eq!(wrap("", 12), [""]);
+    }
+
+    #[test]
+    fn wrap_applies_to_copyright_lines_but_not_license_texts() {
+        let bom = bom(&[("ring", "0.17.5"), ("serde", "1.0.188")]);
+
+        let report = gen_report(CONFIG, &bom, &["--wrap", "20"]).unwrap();
+        assert!(report.contains("\nCopyright 2015-2016\nBrian Smith.\n"));
+        let text = include_str!("../licenses/mit.txt");
+        let long = text.lines().find(|x| x.len() > 20).unwrap();
+        assert!(report.lines().any(|x| x == long));
+    }
+
+    #[test]
+    fn copyright_lines_are_verbatim_unless_normalized() {
+        let bom = bom(&[("ring", "0.17.5")]);
+
+        let report = gen_report(CONFIG, &bom, &[]).unwrap();
+        assert!(report.contains("\nCopyright 2015-2016 Brian Smith.\n"));
+
+        let report = gen_report(CONFIG, &bom, &["--normalize-copyright"]).unwrap();
+        assert!(report.contains("\nCopyright (c) 2015-2016 Brian Smith.\n"));
+    }
+
+    #[test]
+    fn license_dir_receives_the_referenced_texts() {
+        let dir = TempDir::new("license-dir");
+        let licenses = dir.path().join("licenses");
+        let bom = bom(&[("serde", "1.0.188"), ("ring", "0.17.5")]);
+
+        let report =
+            gen_report(CONFIG, &bom, &["--license-dir", licenses.to_str().unwrap()]).unwrap();
+        assert!(!report.contains("==== MIT ===="));
+        for (spdx, text) in [
+            ("ISC", include_str!("../licenses/isc.txt")),
+            ("MIT", include_str!("../licenses/mit.txt")),
+            ("OpenSSL", include_str!("../licenses/openssl.txt")),
+        ] {
+            let path = licenses.join(format!("{spdx}.txt"));
+            assert!(report.contains(&format!("See file: {}\n", path.display())));
+            assert_eq!(std::fs::read_to_string(path).unwrap(), text);
+        }
+    }
+
+    #[test]
+    fn csv_fields_are_quoted_when_needed() {
+        assert_eq!(csv_field("MIT"), "MIT");
+        assert_eq!(
+            csv_field("Copyright 2019, Jane"),
+            "\"Copyright 2019, Jane\""
+        );
+        assert_eq!(csv_field("the \"Software\""), r#""the ""Software""""#);
+        assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+    }
+
+    #[test]
+    fn csv_lists_one_row_per_crate() {
+        let dir = TempDir::new("csv");
+        let csv = dir.path().join("crates.csv");
+        let bom = bom(&[
+            ("serde", "1.0.188"),
+            ("serde", "1.0.190"),
+            ("libc", "0.2.150"),
+        ]);
+
+        gen_report(CONFIG, &bom, &["--csv", csv.to_str().unwrap()]).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(csv).unwrap(),
+            "crate,version,licenses,source,copyright_present\n\
+             libc,0.2.150,MIT,crates.io,false\n\
+             serde,\"1.0.188, 1.0.190\",MIT,crates.io,true\n"
+        );
+    }
+
+    #[test]
+    fn only_the_text_of_the_chosen_license_is_emitted() {
+        let config = CONFIG.replace(
+            r#""id": "ring","#,
+            r#""id": "ring", "combine": "OR", "chosen": "ISC", "chosen_rationale": "approved by legal","#,
+        );
+        let bom = bom(&[("ring", "0.17.5")]);
+
+        let report = gen_report(CONFIG, &bom, &[]).unwrap();
+        assert!(report.contains("The OpenSSL Project"));
+
+        let report = gen_report(&config, &bom, &[]).unwrap();
+        assert!(report.contains("rationale: approved by legal\n"));
+        assert!(report.contains("==== ISC ===="));
+        assert!(!report.contains("The OpenSSL Project"));
+    }
+
+    #[test]
+    fn mpl1_and_mpl2_render_distinctly() {
+        let config = CONFIG
+            .replace(
+                r#"[{"MIT": {"copyright": "NotPresent"}}]"#,
+                r#"["MPLv1.1"]"#,
+            )
+            .replace(r#""licenses": ["OpenSSL","#, r#""licenses": ["MPLv2","#);
+        let bom = bom(&[("libc", "0.2.150"), ("ring", "0.17.5")]);
+
+        let report = gen_report(&config, &bom, &[]).unwrap();
+        assert!(report.contains("license(s): MPL-1.1\n"));
+        assert!(report.contains("license(s): MPL-2.0 AND ISC\n"));
+        assert!(report.contains(crate::config::License::Mpl1.text().as_ref()));
+        assert!(report.contains(crate::config::License::Mpl2.text().as_ref()));
+    }
+
+    #[test]
+    fn attributions_list_each_copyright_once() {
+        let dir = TempDir::new("attributions");
+        let attributions = dir.path().join("ATTRIBUTIONS");
+        let config = CONFIG.replace(
+            r#""licenses": ["OpenSSL","#,
+            r#""licenses": [{"MIT": {"copyright": {"Lines": ["Copyright 2015-2016 Brian Smith."]}}},"#,
+        );
+        let bom = bom(&[("serde", "1.0.188"), ("ring", "0.17.5")]);
+
+        let report = gen_report(
+            &config,
+            &bom,
+            &["--attributions", attributions.to_str().unwrap()],
+        )
+        .unwrap();
+        assert!(!report.contains("Brian Smith"));
+        assert_eq!(
+            std::fs::read_to_string(attributions).unwrap(),
+            "ring\n  Copyright 2015-2016 Brian Smith.\n\n\
+             serde\n  Copyright (c) 2014 The Rust Project Developers\n\n"
+        );
+    }
+
+    #[test]
+    fn copyright_placeholder_can_be_overridden() {
+        let bom = bom(&[("libc", "0.2.150")]);
+
+        let report = gen_report(CONFIG, &bom, &[]).unwrap();
+        assert!(report.contains("\nNo copyright statement was provided by the author"));
+
+        let config = CONFIG.replacen(
+            '{',
+            r#"{"copyright_placeholder": "See the crate sources for the authors.","#,
+            1,
+        );
+        let report = gen_report(&config, &bom, &[]).unwrap();
+        assert!(report.contains("\nSee the crate sources for the authors.\n"));
+        assert!(!report.contains("No copyright statement"));
+    }
+
+    #[test]
+    fn identical_notices_are_printed_once() {
+        let config = CONFIG
+            .replace(
+                r#"[{"MIT": {"copyright": {"Lines": ["Copyright (c) 2014 The Rust Project Developers"]}}}]"#,
+                r#"[{"Apache2": {"notice": ["Shared Project", "Copyright 2020 Shared Authors"]}}]"#,
+            )
+            .replace(
+                r#"[{"MIT": {"copyright": "NotPresent"}}]"#,
+                r#"[{"Apache2": {"notice": ["Shared Project", "Copyright 2020 Shared Authors"]}}]"#,
+            )
+            .replace(
+                r#""licenses": ["OpenSSL","#,
+                r#""licenses": [{"Apache2": {"notice": ["Ring"]}},"#,
+            );
+        let bom = bom(&[
+            ("serde", "1.0.188"),
+            ("libc", "0.2.150"),
+            ("ring", "0.17.5"),
+        ]);
+
+        let report = gen_report(&config, &bom, &[]).unwrap();
+        assert_eq!(report.matches("Copyright 2020 Shared Authors").count(), 1);
+        assert!(report
+            .contains("NOTICE of libc, serde:\n\nShared Project\nCopyright 2020 Shared Authors\n"));
+        assert!(report.contains("NOTICE of ring:\n\nRing\n"));
+        // the notices follow the license texts
+        assert!(report.find("NOTICE of").unwrap() > report.find("Apache License").unwrap());
+    }
+
+    #[test]
+    fn custom_licenses_are_rendered_like_bundled_ones() {
+        let config = CONFIG.replace(
+            r#"[{"MIT": {"copyright": "NotPresent"}}]"#,
+            r#"[{"Custom": {"spdx": "LicenseRef-Foo", "url": "https://example.com/foo", "text": "Foo license text", "copyright": {"Lines": ["Copyright Foo"]}}}]"#,
+        );
+        let report = gen_report(&config, &bom(&[("libc", "0.2.150")]), &[]).unwrap();
+
+        assert!(report.contains("  * LicenseRef-Foo\n      - https://example.com/foo\n"));
+        assert!(report.contains("license(s): LicenseRef-Foo\nCopyright Foo\n"));
+        assert!(report.contains("\nFoo license text"));
+    }
+
+    #[test]
+    fn mixed_expressions_emit_the_texts_of_the_first_alternatives() {
+        let config = CONFIG.replace(
+            r#""id": "ring", "source": "crates.io", "licenses": ["OpenSSL","#,
+            r#""id": "ring", "source": "crates.io", "expression": "(MIT OR OpenSSL) AND ISC", "licenses": ["OpenSSL", {"MIT": {"copyright": "NotPresent"}},"#,
+        );
+        let report = gen_report(&config, &bom(&[("ring", "0.17.5")]), &[]).unwrap();
+
+        assert!(report.contains("license(s): (MIT OR OpenSSL) AND ISC\n"));
+        assert!(report.contains("==== MIT ===="));
+        assert!(report.contains("==== ISC ===="));
+        assert!(!report.contains("The OpenSSL Project"));
+    }
+
+    #[test]
+    fn bom_authors_are_attributed_when_the_copyright_is_missing() {
+        let mut libc = component("libc", "0.2.150");
+        libc["author"] = "The Rust Project Developers".into();
+        let mut serde = component("serde", "1.0.188");
+        serde["publisher"] = "David Tolnay".into();
+
+        let report = gen_report(CONFIG, &bom_with(vec![libc, serde]), &[]).unwrap();
+        assert!(report.contains("attribution: The Rust Project Developers\n"));
+        // serde has a copyright statement, so its publisher is not needed
+        assert!(!report.contains("David Tolnay"));
+    }
+}
@@ -0,0 +1,198 @@
+use crate::report::{LicenseText, Notice, Report};
+
+/// Write the header and the per-crate information as reStructuredText
+pub(crate) fn write_summary<W>(report: &Report, w: &mut W, location: &str) -> std::io::Result<()>
+where
+    W: std::io::Write,
+{
+    heading(w, "Licenses", '=')?;
+    writeln!(w, "{}", escape(&report.intro()))?;
+    writeln!(w)?;
+    for (spdx, info) in report.licenses.iter() {
+        writeln!(
+            w,
+            "* {} - {}",
+            escape(&report.license_label(spdx)),
+            link(info.url)
+        )?;
+        if let Some(note) = info.note {
+            writeln!(w, "  (note: {})", escape(note))?;
+        }
+        if let Some(obligations) = report.obligations(spdx) {
+            writeln!(w, "  (obligations: {})", escape(&obligations))?;
+        }
+    }
+    writeln!(w)?;
+    writeln!(w, "{}", escape(&report.copies_sentence(location)))?;
+    writeln!(w)?;
+
+    heading(w, "Crates", '=')?;
+    for entry in report.crates.iter() {
+        heading(w, &escape(entry.id), '-')?;
+        field(w, "version(s)", &escape(&entry.versions.join(", ")))?;
+        if let Some(targets) = &entry.targets {
+            field(w, "target(s)", &escape(targets))?;
+        }
+        for (target, versions) in entry.target_versions.iter() {
+            field(
+                w,
+                &format!("version(s) in {}", escape(target)),
+                &escape(versions),
+            )?;
+        }
+        field(w, "url", &link(&entry.url))?;
+        field(w, "license(s)", &escape(&entry.expression))?;
+        if let Some(rationale) = entry.rationale {
+            field(w, "rationale", &escape(rationale))?;
+        }
+        if let Some(note) = entry.note {
+            field(w, "note", &escape(note))?;
+        }
+        for author in entry.attribution.iter() {
+            field(w, "attribution", &escape(author))?;
+        }
+        writeln!(w)?;
+
+        // a line block keeps each copyright statement on its own line
+        if !entry.copyright.is_empty() {
+            for line in entry.copyright.iter() {
+                writeln!(w, "| {}", escape(line))?;
+            }
+            writeln!(w)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Write the license texts as reStructuredText literal blocks
+pub(crate) fn write_texts<W>(w: &mut W, texts: &[(&str, LicenseText)]) -> std::io::Result<()>
+where
+    W: std::io::Write,
+{
+    heading(w, "License texts", '=')?;
+    for (spdx, text) in texts {
+        heading(w, &escape(spdx), '-')?;
+        match text {
+            LicenseText::Inline(text) => {
+                writeln!(w, "::")?;
+                writeln!(w)?;
+                for line in text.lines() {
+                    if line.trim().is_empty() {
+                        writeln!(w)?;
+                    } else {
+                        writeln!(w, "    {line}")?;
+                    }
+                }
+            }
+            LicenseText::File(path) => writeln!(w, "See file: ``{}``", path.display())?,
+        }
+        writeln!(w)?;
+    }
+    Ok(())
+}
+
+/// Write the NOTICE files as line blocks after the license texts
+pub(crate) fn write_notices<W>(w: &mut W, notices: &[Notice]) -> std::io::Result<()>
+where
+    W: std::io::Write,
+{
+    if notices.is_empty() {
+        return Ok(());
+    }
+    heading(w, "NOTICE", '=')?;
+    for notice in notices {
+        heading(
+            w,
+            &format!("NOTICE of {}", escape(&notice.crates.join(", "))),
+            '-',
+        )?;
+        for line in notice.lines {
+            if line.trim().is_empty() {
+                writeln!(w, "|")?;
+            } else {
+                writeln!(w, "| {}", escape(line))?;
+            }
+        }
+        writeln!(w)?;
+    }
+    Ok(())
+}
+
+/// Write a section title, underlined with `underline`
+fn heading<W>(w: &mut W, title: &str, underline: char) -> std::io::Result<()>
+where
+    W: std::io::Write,
+{
+    writeln!(w, "{title}")?;
+    writeln!(w, "{}", underline.to_string().repeat(title.chars().count()))?;
+    writeln!(w)
+}
+
+/// Write an entry of a field list
+fn field<W>(w: &mut W, name: &str, value: &str) -> std::io::Result<()>
+where
+    W: std::io::Write,
+{
+    writeln!(w, ":{name}: {value}")
+}
+
+/// Anonymous hyperlink whose text is the URL itself
+fn link(url: &str) -> String {
+    format!("`<{url}>`__")
+}
+
+/// Escape characters that would otherwise start inline markup, a list or a field
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for (i, c) in text.chars().enumerate() {
+        let special = matches!(c, '\\' | '*' | '`' | '_' | '|' | '[' | ']' | '<' | '>')
+            || (i == 0 && matches!(c, '-' | '+' | '#' | '.' | ':'));
+        if special {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{bom, gen_report, CONFIG};
+
+    #[test]
+    fn escapes_inline_markup() {
+        assert_eq!(escape("serde_json"), "serde\\_json");
+        assert_eq!(
+            escape("*not* `code` <url>"),
+            "\\*not\\* \\`code\\` \\<url\\>"
+        );
+        assert_eq!(escape("- not a list - item"), "\\- not a list - item");
+        assert_eq!(escape("MIT"), "MIT");
+    }
+
+    #[test]
+    fn report_is_structured_as_rst() {
+        let config = CONFIG.replace("\"serde\"", "\"serde_json\"");
+        let bom = bom(&[("serde_json", "1.0.108"), ("ring", "0.17.5")]);
+        let report = gen_report(&config, &bom, &["--format", "rst"]).unwrap();
+
+        assert!(report.starts_with("Licenses\n========\n\n"));
+        assert!(report.contains("\nserde\\_json\n-----------\n\n:version(s): 1.0.108\n"));
+        assert!(report.contains(":url: `<https://crates.io/crates/serde_json>`__\n"));
+        assert!(report.contains("\n| Copyright 2015-2016 Brian Smith.\n"));
+        assert!(report.contains("\nMIT\n---\n\n::\n\n    ===="));
+        assert!(report.contains("\n    Permission is hereby granted"));
+
+        // every section title is underlined to its full length
+        let lines: Vec<&str> = report.lines().collect();
+        for pair in lines.windows(2) {
+            if let Some(c) = pair[1].chars().next().filter(|x| matches!(x, '=' | '-')) {
+                if !pair[0].is_empty() && pair[1].chars().all(|x| x == c) {
+                    assert_eq!(pair[0].chars().count(), pair[1].chars().count());
+                }
+            }
+        }
+    }
+}
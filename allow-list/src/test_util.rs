@@ -0,0 +1,157 @@
+use crate::cli::{BomOptions, ConfigOptions, ReportOptions};
+use clap::Parser;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Configuration in which serde and libc are MIT, ring is OpenSSL AND ISC and cc is build-only
+pub(crate) const CONFIG: &str = r#"{
+    "build_only": ["cc"],
+    "vendor": {},
+    "third_party": {
+        "serde": {"id": "serde", "source": "crates.io", "licenses": [{"MIT": {"copyright": {"Lines": ["Copyright (c) 2014 The Rust Project Developers"]}}}]},
+        "libc": {"id": "libc", "source": "crates.io", "licenses": [{"MIT": {"copyright": "NotPresent"}}]},
+        "ring": {"id": "ring", "source": "crates.io", "licenses": ["OpenSSL", {"ISC": {"copyright": {"Lines": ["Copyright 2015-2016 Brian Smith."]}}}]}
+    }
+}"#;
+
+/// A group of options along with the BOM options, which other groups may refer to
+#[derive(Parser)]
+struct Options<T: clap::Args> {
+    #[clap(flatten)]
+    options: T,
+    #[clap(flatten)]
+    bom_options: BomOptions,
+}
+
+/// Parse a group of command line options as clap would, without the name of the program
+pub(crate) fn args<T>(args: &[&str]) -> T
+where
+    T: clap::Args + clap::FromArgMatches,
+{
+    bom_args(args).0
+}
+
+/// Parse a group of command line options along with the BOM options
+pub(crate) fn bom_args<T>(args: &[&str]) -> (T, BomOptions)
+where
+    T: clap::Args + clap::FromArgMatches,
+{
+    let options =
+        Options::<T>::try_parse_from(std::iter::once("test").chain(args.iter().copied())).unwrap();
+    (options.options, options.bom_options)
+}
+
+/// Options selecting the configurations at `paths`
+pub(crate) fn config_options(paths: &[&Path]) -> ConfigOptions {
+    ConfigOptions {
+        config_path: paths.iter().map(|x| x.to_path_buf()).collect(),
+        force: false,
+    }
+}
+
+/// Empty directory that is unique to a test, removed when the value is dropped
+pub(crate) struct TempDir(PathBuf);
+
+impl TempDir {
+    pub(crate) fn new(name: &str) -> Self {
+        static COUNT: AtomicUsize = AtomicUsize::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "allow-list-{name}-{}-{}",
+            std::process::id(),
+            COUNT.fetch_add(1, Ordering::Relaxed)
+        ));
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::create_dir_all(&path).unwrap();
+        Self(path)
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.0
+    }
+
+    /// Write a file relative to the directory, creating its parents, and return its path
+    pub(crate) fn write(&self, relative: &str, content: &str) -> PathBuf {
+        let path = self.0.join(relative);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// A crates.io library component of a CycloneDX BOM
+pub(crate) fn component(name: &str, version: &str) -> serde_json::Value {
+    serde_json::json!({
+        "type": "library",
+        "name": name,
+        "version": version,
+        "purl": format!("pkg:cargo/{name}@{version}"),
+    })
+}
+
+/// CycloneDX 1.4 JSON BOM with the given components
+pub(crate) fn bom_with(components: Vec<serde_json::Value>) -> String {
+    serde_json::json!({
+        "bomFormat": "CycloneDX",
+        "specVersion": "1.4",
+        "version": 1,
+        "components": components,
+    })
+    .to_string()
+}
+
+/// CycloneDX 1.4 JSON BOM listing crates.io components by name and version
+pub(crate) fn bom(components: &[(&str, &str)]) -> String {
+    bom_with(
+        components
+            .iter()
+            .map(|(name, version)| component(name, version))
+            .collect(),
+    )
+}
+
+/// Generate the report of `gen-licenses` for a configuration and a BOM, given the options of the command
+pub(crate) fn gen_report(
+    config: &str,
+    bom: &str,
+    options: &[&str],
+) -> Result<String, anyhow::Error> {
+    let dir = TempDir::new("report");
+    let config = dir.write("config.json", config);
+    let bom = dir.write("bom.json", bom);
+    let (options, bom_options): (ReportOptions, BomOptions) = bom_args(options);
+    let mut out = Vec::new();
+    crate::licenses::gen_licenses(
+        &bom,
+        &config_options(&[&config]),
+        &bom_options,
+        &options,
+        &mut out,
+    )?;
+    Ok(String::from_utf8(out).unwrap())
+}
+
+/// Answer a single HTTP request on a local port with the given status line and body, returning the URL to request
+#[cfg(feature = "network")]
+pub(crate) fn serve(status: &'static str, body: String) -> String {
+    use std::io::{Read, Write};
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/", listener.local_addr().unwrap());
+    std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut buf = [0; 1024];
+        let _ = stream.read(&mut buf);
+        let _ = write!(
+            stream,
+            "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        );
+    });
+    url
+}
@@ -0,0 +1,97 @@
+use std::collections::BTreeSet;
+use std::time::Duration;
+
+/// Download the body of a URL, failing on any network error or error status
+pub(crate) fn fetch(url: &str) -> Result<impl std::io::Read, anyhow::Error> {
+    let response = ureq::AgentBuilder::new()
+        .timeout(Duration::from_secs(30))
+        .build()
+        .get(url)
+        .call()
+        .map_err(|err| anyhow::Error::msg(format!("Unable to fetch {err}")))?;
+    Ok(response.into_reader())
+}
+
+/// Checks that URLs in the report are reachable, remembering the result for each URL
+pub(crate) struct UrlChecker {
+    agent: ureq::Agent,
+    checked: BTreeSet<String>,
+}
+
+impl UrlChecker {
+    pub(crate) fn new() -> Self {
+        Self {
+            agent: ureq::AgentBuilder::new()
+                .timeout(Duration::from_secs(10))
+                .build(),
+            checked: BTreeSet::new(),
+        }
+    }
+
+    /// Issue a HEAD request for the URL and describe the problem if it does not succeed
+    ///
+    /// A URL that was already checked is not requested again and reports no problem,
+    /// network errors are only meant to produce a warning and never fail the report
+    pub(crate) fn check(&mut self, url: &str) -> Option<String> {
+        if !self.checked.insert(url.to_string()) {
+            return None;
+        }
+
+        match self.agent.head(url).call() {
+            Ok(_) => None,
+            Err(ureq::Error::Status(code, _)) => Some(format!("{url} returned HTTP status {code}")),
+            Err(err) => Some(format!("unable to check URL: {err}")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::serve;
+    use std::sync::{Arc, Mutex};
+
+    /// Answer every request with the given status, returning the URL and the request lines received
+    fn serve_all(status: &'static str) -> (String, Arc<Mutex<Vec<String>>>) {
+        use std::io::{BufRead, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let received = requests.clone();
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let mut line = String::new();
+                let _ = std::io::BufReader::new(&stream).read_line(&mut line);
+                received.lock().unwrap().push(line.trim_end().to_string());
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 {status}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                );
+            }
+        });
+        (url, requests)
+    }
+
+    #[test]
+    fn error_status_is_a_problem_reported_once_per_url() {
+        let (url, requests) = serve_all("404 Not Found");
+
+        let mut checker = UrlChecker::new();
+        let problems: Vec<String> = [&url, &url]
+            .iter()
+            .filter_map(|x| checker.check(x))
+            .collect();
+
+        assert_eq!(problems, [format!("{url} returned HTTP status 404")]);
+        assert_eq!(*requests.lock().unwrap(), ["HEAD / HTTP/1.1"]);
+    }
+
+    #[test]
+    fn fetch_fails_on_error_status() {
+        let url = serve("500 Internal Server Error", String::new());
+
+        let err = fetch(&url).err().unwrap();
+        assert!(err.to_string().starts_with("Unable to fetch"));
+    }
+}
@@ -0,0 +1,144 @@
+use std::ffi::OsStr;
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicBool, AtomicU8, AtomicUsize, Ordering};
+
+static COUNT: AtomicUsize = AtomicUsize::new(0);
+static COLOR: AtomicBool = AtomicBool::new(false);
+static VERBOSITY: AtomicU8 = AtomicU8::new(0);
+
+/// Enable colored warnings if stderr is a terminal, unless disabled by flag or the NO_COLOR variable
+pub(crate) fn init_color(no_color: bool) {
+    COLOR.store(
+        use_color(
+            no_color,
+            std::env::var_os("NO_COLOR").as_deref(),
+            std::io::stderr().is_terminal(),
+        ),
+        Ordering::Relaxed,
+    );
+}
+
+/// A non-empty NO_COLOR variable disables color like the flag does
+fn use_color(no_color: bool, no_color_var: Option<&OsStr>, is_terminal: bool) -> bool {
+    let no_color = no_color || no_color_var.is_some_and(|x| !x.is_empty());
+    !no_color && is_terminal
+}
+
+/// Set how many levels of diagnostics beyond warnings are printed, i.e. the number of `-v` flags
+pub(crate) fn init_verbosity(level: u8) {
+    VERBOSITY.store(level, Ordering::Relaxed);
+}
+
+/// Print a progress message to stderr if at least `-v` was given
+pub(crate) fn info(msg: impl std::fmt::Display) {
+    if let Some(line) = diagnostic(VERBOSITY.load(Ordering::Relaxed), 1, "info", msg) {
+        eprintln!("{line}");
+    }
+}
+
+/// Print a detailed message, e.g. about a skipped component, to stderr if `-vv` was given
+pub(crate) fn debug(msg: impl std::fmt::Display) {
+    if let Some(line) = diagnostic(VERBOSITY.load(Ordering::Relaxed), 2, "debug", msg) {
+        eprintln!("{line}");
+    }
+}
+
+/// The line to print for a message that requires `level` at the given verbosity, if any
+fn diagnostic(
+    verbosity: u8,
+    level: u8,
+    prefix: &str,
+    msg: impl std::fmt::Display,
+) -> Option<String> {
+    (verbosity >= level).then(|| format!("{prefix}: {msg}"))
+}
+
+/// Print a note to stderr, e.g. about information left out of the report on request
+pub(crate) fn note(msg: impl std::fmt::Display) {
+    eprintln!("note: {msg}");
+}
+
+/// Print a warning to stderr and record that it was emitted
+pub(crate) fn warn(msg: impl std::fmt::Display) {
+    COUNT.fetch_add(1, Ordering::Relaxed);
+    if COLOR.load(Ordering::Relaxed) {
+        eprintln!("\x1b[33mwarning\x1b[0m: {msg}");
+    } else {
+        eprintln!("warning: {msg}");
+    }
+}
+
+/// Number of warnings emitted so far
+pub(crate) fn count() -> usize {
+    COUNT.load(Ordering::Relaxed)
+}
+
+/// Fail if `werror` is set and any warning was emitted
+pub(crate) fn check(werror: bool) -> Result<(), anyhow::Error> {
+    if werror && count() > 0 {
+        return Err(anyhow::Error::msg(format!(
+            "{} warning(s) emitted and --werror is set",
+            count()
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_color_disables_color() {
+        assert!(use_color(false, None, true));
+        assert!(!use_color(false, Some(OsStr::new("1")), true));
+        assert!(!use_color(true, None, true));
+        assert!(!use_color(false, None, false));
+        // an empty variable is ignored, as the NO_COLOR convention requires
+        assert!(use_color(false, Some(OsStr::new("")), true));
+    }
+
+    #[test]
+    fn verbose_flags_surface_diagnostics_that_are_silent_by_default() {
+        let verbosity = |args: &[&str]| {
+            <crate::cli::Cli as clap::Parser>::try_parse_from(
+                ["allow-list"]
+                    .iter()
+                    .chain(args)
+                    .chain(&["fmt-config", "config.json"]),
+            )
+            .unwrap()
+            .verbose
+        };
+        let skip = "skipping cc 1.0.83: listed in build_only";
+
+        let quiet = verbosity(&[]);
+        assert_eq!(diagnostic(quiet, 1, "info", "loaded"), None);
+        assert_eq!(diagnostic(quiet, 2, "debug", skip), None);
+
+        let verbose = verbosity(&["-v"]);
+        assert_eq!(
+            diagnostic(verbose, 1, "info", "loaded").as_deref(),
+            Some("info: loaded")
+        );
+        assert_eq!(diagnostic(verbose, 2, "debug", skip), None);
+
+        let very_verbose = verbosity(&["-vv"]);
+        assert_eq!(
+            diagnostic(very_verbose, 2, "debug", skip),
+            Some(format!("debug: {skip}"))
+        );
+    }
+
+    #[test]
+    fn werror_fails_once_a_warning_was_emitted() {
+        warn("test warning");
+
+        assert!(count() > 0);
+        assert!(check(false).is_ok());
+        assert!(check(true)
+            .unwrap_err()
+            .to_string()
+            .ends_with("warning(s) emitted and --werror is set"));
+    }
+}
@@ -0,0 +1,53 @@
+// the dependencies of the binary are not used by this test
+#![allow(unused_crate_dependencies)]
+
+use std::process::{Command, Output};
+
+const CONFIG: &str = r#"{"build_only":[],"vendor":{},"third_party":{
+    "libc":{"id":"libc","source":"crates.io","licenses":[{"MIT":{"copyright":"NotPresent"}}]}}}"#;
+
+const BOM: &str = r#"{"bomFormat":"CycloneDX","specVersion":"1.4","version":1,"components":[
+    {"type":"library","name":"libc","version":"0.2.150","purl":"pkg:cargo/libc@0.2.150"}]}"#;
+
+/// Run gen-licenses on a crate without a copyright statement, with extra global arguments
+fn gen_licenses(name: &str, global_args: &[&str]) -> Output {
+    let dir = std::env::temp_dir().join(format!("allow-list-{name}-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let config = dir.join("config.json");
+    let bom = dir.join("bom.json");
+    std::fs::write(&config, CONFIG).unwrap();
+    std::fs::write(&bom, BOM).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_allow-list"))
+        .args(global_args)
+        .arg("gen-licenses")
+        .arg("-c")
+        .arg(&config)
+        .arg("-b")
+        .arg(&bom)
+        .output()
+        .unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+    output
+}
+
+#[test]
+fn missing_copyright_is_a_warning() {
+    let output = gen_licenses("warning", &[]);
+
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "warning: no copyright statement for the MIT license(s) of libc\n"
+    );
+}
+
+#[test]
+fn missing_copyright_fails_with_werror() {
+    let output = gen_licenses("werror", &["--werror"]);
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.starts_with("warning: no copyright statement for the MIT license(s) of libc\n"));
+    assert!(stderr.contains("1 warning(s) emitted and --werror is set"));
+}